@@ -1,16 +1,14 @@
 //! # Example: Filter Stations
 //!
 //! This example shows how to filter trains based on the station's state.
-use amtrak_api::Client;
+use amtrak_api::{address::StateOrProvince, Client};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Client::new()
         .stations()
         .await?
-        .0
-        .values()
-        .filter(|station| station.state == "PA")
+        .in_state(StateOrProvince::Pennsylvania)
         .for_each(|station| {
             println!("Station \"{}\" is in PA", station.name);
         });
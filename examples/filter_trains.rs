@@ -2,21 +2,16 @@
 //!
 //! This example shows how to filter trains based on the route name and then
 //! determine what station the train is currently in route to.
-use amtrak_api::{responses::TrainStatus, Client};
+use amtrak_api::{query::TrainQuery, responses::TrainStatus, Client};
 use chrono::{Local, Utc};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    Client::new()
-        .trains()
-        .await?
-        .0
-        .into_iter()
-        .flat_map(|(_, trains)| {
-            trains
-                .into_iter()
-                .filter(|train| train.route_name == "Keystone")
-        })
+    let response = Client::new().trains().await?;
+
+    TrainQuery::new()
+        .route_name("Keystone")
+        .run(&response)
         .map(|train| {
             let enroute_information = train
                 .stations
@@ -0,0 +1,42 @@
+//! # Example: Refresh Stations
+//!
+//! This example refreshes the station snapshot embedded by the
+//! `bundled-stations` feature. It reads a saved `/stations` response when a
+//! path is provided and fetches the stations from the API otherwise, then
+//! writes the snapshot to `data/stations.json` (or the second argument).
+//!
+//! ```sh
+//! cargo run --example refresh_stations
+//! cargo run --example refresh_stations -- saved-stations.json data/stations.json
+//! ```
+use std::collections::BTreeMap;
+
+use amtrak_api::{responses::StationResponse, Client};
+
+const DEFAULT_OUTPUT: &str = "data/stations.json";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let response: StationResponse = match args.next() {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Client::new().stations().await?,
+    };
+    let output = args.next().unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+
+    // The trains serving a station change all the time and are left out of
+    // the snapshot, the stations are sorted to keep the diffs readable.
+    let stations: BTreeMap<_, _> = response
+        .0
+        .into_iter()
+        .map(|(code, mut station)| {
+            station.trains.clear();
+            (code, station)
+        })
+        .collect();
+
+    std::fs::write(&output, serde_json::to_string_pretty(&stations)? + "\n")?;
+    println!("Wrote {} stations to {}", stations.len(), output);
+
+    Ok(())
+}
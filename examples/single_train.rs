@@ -18,7 +18,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(trains) => match trains.len() {
             1 => {
                 let phl_station = trains
-                    .get(0)
+                    .first()
                     .unwrap()
                     .stations
                     .iter()
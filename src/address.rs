@@ -0,0 +1,351 @@
+//! Typed station addresses
+//!
+//! The API returns the address of a [`Station`] as free form strings. This
+//! module parses the [`state`] into a [`StateOrProvince`], validates the
+//! [`zip`] against the postal code format of its country and formats the
+//! whole [`Address`] on one or several lines.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{address::StateOrProvince, Client};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let stations = Client::new().stations().await?;
+//!
+//!     for station in stations.in_state(StateOrProvince::Pennsylvania) {
+//!         println!("{}: {}", station.name, station.address());
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`state`]: Station::state
+//! [`zip`]: Station::zip
+
+use std::{fmt, str::FromStr};
+
+use serde::Serialize;
+
+use crate::responses::{Station, StationResponse};
+
+/// The countries served by Amtrak stations
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Country {
+    /// The United States of America
+    UnitedStates,
+
+    /// Canada
+    Canada,
+}
+
+impl Country {
+    /// Returns `true` if the code follows the postal code format of the
+    /// country: a five digit ZIP code optionally followed by a four digit
+    /// extension (`19104` or `19104-2801`) in the United States and `A1A 1A1`
+    /// (the space being optional) in Canada.
+    pub fn is_valid_postal_code(&self, code: &str) -> bool {
+        let code = code.trim().as_bytes();
+        let digits = |bytes: &[u8]| bytes.iter().all(u8::is_ascii_digit);
+
+        match self {
+            Self::UnitedStates => match code.len() {
+                5 => digits(code),
+                10 => digits(&code[..5]) && code[5] == b'-' && digits(&code[6..]),
+                _ => false,
+            },
+            Self::Canada => {
+                let code = match code.len() {
+                    6 => code.to_vec(),
+                    7 if code[3] == b' ' => [&code[..3], &code[4..]].concat(),
+                    _ => return false,
+                };
+
+                code.iter().enumerate().all(|(index, byte)| {
+                    if index % 2 == 0 {
+                        byte.is_ascii_uppercase()
+                    } else {
+                        byte.is_ascii_digit()
+                    }
+                })
+            }
+        }
+    }
+}
+
+macro_rules! states_and_provinces {
+    ($($variant:ident => ($code:literal, $name:literal, $country:ident),)*) => {
+        /// A US state (or the District of Columbia) or a Canadian province or
+        /// territory
+        #[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum StateOrProvince {
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+        }
+
+        impl StateOrProvince {
+            /// Every state and province
+            pub const ALL: &'static [StateOrProvince] = &[$(Self::$variant,)*];
+
+            /// Returns the two letter postal abbreviation, for example `PA`
+            pub fn code(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $code,)*
+                }
+            }
+
+            /// Returns the full name, for example `Pennsylvania`
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            /// Returns the country the state or province belongs to
+            pub fn country(&self) -> Country {
+                match self {
+                    $(Self::$variant => Country::$country,)*
+                }
+            }
+        }
+    };
+}
+
+states_and_provinces! {
+    Alabama => ("AL", "Alabama", UnitedStates),
+    Alaska => ("AK", "Alaska", UnitedStates),
+    Arizona => ("AZ", "Arizona", UnitedStates),
+    Arkansas => ("AR", "Arkansas", UnitedStates),
+    California => ("CA", "California", UnitedStates),
+    Colorado => ("CO", "Colorado", UnitedStates),
+    Connecticut => ("CT", "Connecticut", UnitedStates),
+    Delaware => ("DE", "Delaware", UnitedStates),
+    DistrictOfColumbia => ("DC", "District of Columbia", UnitedStates),
+    Florida => ("FL", "Florida", UnitedStates),
+    Georgia => ("GA", "Georgia", UnitedStates),
+    Hawaii => ("HI", "Hawaii", UnitedStates),
+    Idaho => ("ID", "Idaho", UnitedStates),
+    Illinois => ("IL", "Illinois", UnitedStates),
+    Indiana => ("IN", "Indiana", UnitedStates),
+    Iowa => ("IA", "Iowa", UnitedStates),
+    Kansas => ("KS", "Kansas", UnitedStates),
+    Kentucky => ("KY", "Kentucky", UnitedStates),
+    Louisiana => ("LA", "Louisiana", UnitedStates),
+    Maine => ("ME", "Maine", UnitedStates),
+    Maryland => ("MD", "Maryland", UnitedStates),
+    Massachusetts => ("MA", "Massachusetts", UnitedStates),
+    Michigan => ("MI", "Michigan", UnitedStates),
+    Minnesota => ("MN", "Minnesota", UnitedStates),
+    Mississippi => ("MS", "Mississippi", UnitedStates),
+    Missouri => ("MO", "Missouri", UnitedStates),
+    Montana => ("MT", "Montana", UnitedStates),
+    Nebraska => ("NE", "Nebraska", UnitedStates),
+    Nevada => ("NV", "Nevada", UnitedStates),
+    NewHampshire => ("NH", "New Hampshire", UnitedStates),
+    NewJersey => ("NJ", "New Jersey", UnitedStates),
+    NewMexico => ("NM", "New Mexico", UnitedStates),
+    NewYork => ("NY", "New York", UnitedStates),
+    NorthCarolina => ("NC", "North Carolina", UnitedStates),
+    NorthDakota => ("ND", "North Dakota", UnitedStates),
+    Ohio => ("OH", "Ohio", UnitedStates),
+    Oklahoma => ("OK", "Oklahoma", UnitedStates),
+    Oregon => ("OR", "Oregon", UnitedStates),
+    Pennsylvania => ("PA", "Pennsylvania", UnitedStates),
+    RhodeIsland => ("RI", "Rhode Island", UnitedStates),
+    SouthCarolina => ("SC", "South Carolina", UnitedStates),
+    SouthDakota => ("SD", "South Dakota", UnitedStates),
+    Tennessee => ("TN", "Tennessee", UnitedStates),
+    Texas => ("TX", "Texas", UnitedStates),
+    Utah => ("UT", "Utah", UnitedStates),
+    Vermont => ("VT", "Vermont", UnitedStates),
+    Virginia => ("VA", "Virginia", UnitedStates),
+    Washington => ("WA", "Washington", UnitedStates),
+    WestVirginia => ("WV", "West Virginia", UnitedStates),
+    Wisconsin => ("WI", "Wisconsin", UnitedStates),
+    Wyoming => ("WY", "Wyoming", UnitedStates),
+    Alberta => ("AB", "Alberta", Canada),
+    BritishColumbia => ("BC", "British Columbia", Canada),
+    Manitoba => ("MB", "Manitoba", Canada),
+    NewBrunswick => ("NB", "New Brunswick", Canada),
+    NewfoundlandAndLabrador => ("NL", "Newfoundland and Labrador", Canada),
+    NorthwestTerritories => ("NT", "Northwest Territories", Canada),
+    NovaScotia => ("NS", "Nova Scotia", Canada),
+    Nunavut => ("NU", "Nunavut", Canada),
+    Ontario => ("ON", "Ontario", Canada),
+    PrinceEdwardIsland => ("PE", "Prince Edward Island", Canada),
+    Quebec => ("QC", "Quebec", Canada),
+    Saskatchewan => ("SK", "Saskatchewan", Canada),
+    Yukon => ("YT", "Yukon", Canada),
+}
+
+/// The error returned when a string is neither the postal abbreviation nor
+/// the name of a state or province
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStateOrProvinceError(String);
+
+impl fmt::Display for ParseStateOrProvinceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown state or province \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for ParseStateOrProvinceError {}
+
+impl FromStr for StateOrProvince {
+    type Err = ParseStateOrProvinceError;
+
+    /// Parses the postal abbreviation (`PA`) or the name (`Pennsylvania`),
+    /// ignoring case
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+
+        Self::ALL
+            .iter()
+            .find(|state| {
+                state.code().eq_ignore_ascii_case(trimmed)
+                    || state.name().eq_ignore_ascii_case(trimmed)
+            })
+            .copied()
+            .ok_or_else(|| ParseStateOrProvinceError(value.to_string()))
+    }
+}
+
+impl fmt::Display for StateOrProvince {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// The postal address of a station
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Address {
+    /// The street lines of the address, blank lines are left out
+    pub lines: Vec<String>,
+
+    /// The city
+    pub city: String,
+
+    /// The state or province as returned by the API
+    pub state: String,
+
+    /// The ZIP or postal code
+    pub zip: String,
+}
+
+impl Address {
+    /// Returns the parsed [`state`], or `None` if it is not a known state or
+    /// province
+    ///
+    /// [`state`]: Self::state
+    pub fn state_or_province(&self) -> Option<StateOrProvince> {
+        self.state.parse().ok()
+    }
+
+    /// Returns the country of the address, if its state is known
+    pub fn country(&self) -> Option<Country> {
+        self.state_or_province().map(|state| state.country())
+    }
+
+    /// Returns `true` if the [`zip`] follows the postal code format of the
+    /// country of the address, either format is accepted when the country is
+    /// unknown (see [`Country::is_valid_postal_code`]).
+    ///
+    /// [`zip`]: Self::zip
+    pub fn is_zip_valid(&self) -> bool {
+        match self.country() {
+            Some(country) => country.is_valid_postal_code(&self.zip),
+            None => [Country::UnitedStates, Country::Canada]
+                .iter()
+                .any(|country| country.is_valid_postal_code(&self.zip)),
+        }
+    }
+
+    /// Formats the address on a single line, for example
+    /// `2955 Market Street, Philadelphia, PA 19104`
+    pub fn single_line(&self) -> String {
+        let mut parts = self.lines.clone();
+        parts.push(self.locality());
+        parts.retain(|part| !part.is_empty());
+        parts.join(", ")
+    }
+
+    /// Formats the address as it would be written on an envelope, one line
+    /// per street line followed by the city, state and ZIP code
+    pub fn multiline(&self) -> String {
+        let mut parts = self.lines.clone();
+        parts.push(self.locality());
+        parts.retain(|part| !part.is_empty());
+        parts.join("\n")
+    }
+
+    /// Returns the `City, ST 12345` line
+    fn locality(&self) -> String {
+        let region = [self.state.as_str(), self.zip.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        [self.city.as_str(), region.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.single_line())
+    }
+}
+
+impl Station {
+    /// Returns the typed address of this station
+    ///
+    /// Every field is trimmed, the API pads empty address lines with a
+    /// space.
+    pub fn address(&self) -> Address {
+        Address {
+            lines: [&self.address1, &self.address2]
+                .into_iter()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            city: self.city.trim().to_string(),
+            state: self.state.trim().to_string(),
+            zip: self.zip.trim().to_string(),
+        }
+    }
+
+    /// Returns the parsed [`state`] of this station, or `None` if it is not a
+    /// known state or province
+    ///
+    /// [`state`]: Self::state
+    pub fn state_or_province(&self) -> Option<StateOrProvince> {
+        self.state.parse().ok()
+    }
+}
+
+impl StationResponse {
+    /// Returns the stations located in the provided state or province
+    pub fn in_state(&self, state: StateOrProvince) -> impl Iterator<Item = &Station> {
+        self.0
+            .values()
+            .filter(move |station| station.state_or_province() == Some(state))
+    }
+
+    /// Returns the stations located in the provided country
+    pub fn in_country(&self, country: Country) -> impl Iterator<Item = &Station> {
+        self.0.values().filter(move |station| {
+            station
+                .state_or_province()
+                .is_some_and(|state| state.country() == country)
+        })
+    }
+}
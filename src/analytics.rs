@@ -0,0 +1,265 @@
+//! On-time performance analytics
+//!
+//! A [`Report`] summarizes the runs kept in a [`Store`]: the on-time
+//! performance (OTP) and the distribution of delays per route, per train
+//! number, per station and per day of the week, as well as where along each
+//! route the delays are introduced.
+//!
+//! Every station a run actually reached (with a [`Station`] or [`Departed`]
+//! status) and whose delay is known counts as one sample. A sample is on time
+//! when the train arrived at most [`threshold_minutes`] late.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{analytics::Report, storage::Store};
+//! use chrono::{Duration, Utc};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let store = Store::open("history.sqlite")?;
+//!     let runs = store.all_runs(Utc::now() - Duration::days(30))?;
+//!     let report = Report::from_runs(&runs, Report::DEFAULT_THRESHOLD_MINUTES);
+//!
+//!     for group in report.by_route {
+//!         println!("{}: {:.1}%", group.key, group.summary.on_time_performance * 100.0);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`Store`]: crate::storage::Store
+//! [`Station`]: TrainStatus::Station
+//! [`Departed`]: TrainStatus::Departed
+//! [`threshold_minutes`]: Report::threshold_minutes
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Weekday};
+use serde::Serialize;
+
+use crate::{export::StopEvent, responses::TrainStatus, storage::Run};
+
+/// Statistics over a set of delay samples
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Summary {
+    /// Number of samples
+    pub samples: usize,
+
+    /// Number of samples that were on time
+    pub on_time: usize,
+
+    /// The share of samples that were on time, between `0.0` and `1.0`
+    pub on_time_performance: f64,
+
+    /// The average delay in minutes, a negative value means early
+    pub mean_delay_minutes: f64,
+
+    /// The median delay in minutes
+    pub p50_delay_minutes: i64,
+
+    /// The 90th percentile of the delay in minutes
+    pub p90_delay_minutes: i64,
+
+    /// The 95th percentile of the delay in minutes
+    pub p95_delay_minutes: i64,
+
+    /// The largest delay in minutes
+    pub max_delay_minutes: i64,
+}
+
+impl Summary {
+    fn new(mut delays: Vec<i64>, threshold_minutes: i64) -> Option<Self> {
+        if delays.is_empty() {
+            return None;
+        }
+        delays.sort_unstable();
+
+        let samples = delays.len();
+        let on_time = delays
+            .iter()
+            .filter(|delay| **delay <= threshold_minutes)
+            .count();
+
+        Some(Self {
+            samples,
+            on_time,
+            on_time_performance: on_time as f64 / samples as f64,
+            mean_delay_minutes: delays.iter().sum::<i64>() as f64 / samples as f64,
+            p50_delay_minutes: percentile(&delays, 50),
+            p90_delay_minutes: percentile(&delays, 90),
+            p95_delay_minutes: percentile(&delays, 95),
+            max_delay_minutes: delays[samples - 1],
+        })
+    }
+}
+
+/// Returns the nearest rank percentile of the sorted, non empty, samples
+fn percentile(sorted: &[i64], percent: usize) -> i64 {
+    let rank = (percent * sorted.len()).div_ceil(100);
+    sorted[rank.saturating_sub(1)]
+}
+
+/// The [`Summary`] of the samples sharing the same key
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Group<K> {
+    /// The route name, train number, station code or weekday of the samples
+    pub key: K,
+
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// The delay a route introduces between two consecutive stations
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SegmentDelay {
+    /// The route the segment belongs to
+    pub route_name: String,
+
+    /// The station the segment starts at
+    pub from_station: String,
+
+    /// The station the segment ends at
+    pub to_station: String,
+
+    /// Number of runs that reached both stations with a known delay
+    pub samples: usize,
+
+    /// The average change in delay between both stations in minutes, a
+    /// negative value means trains make up time on this segment.
+    pub mean_delay_introduced_minutes: f64,
+}
+
+/// On-time performance of a set of runs
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Report {
+    /// A train arriving at most this many minutes late is on time
+    pub threshold_minutes: i64,
+
+    /// The summary of every sample, if there are any
+    pub overall: Option<Summary>,
+
+    /// Summaries per route name
+    pub by_route: Vec<Group<String>>,
+
+    /// Summaries per train number
+    pub by_train_num: Vec<Group<u32>>,
+
+    /// Summaries per station code
+    pub by_station: Vec<Group<String>>,
+
+    /// Summaries per day of the week the runs departed their origin, from
+    /// Monday to Sunday
+    pub by_weekday: Vec<Group<Weekday>>,
+
+    /// The segments of every route, the ones introducing the most delay
+    /// first
+    pub segments: Vec<SegmentDelay>,
+}
+
+/// Returns the delay of a stop the train actually reached
+fn reached_delay(event: &StopEvent) -> Option<i64> {
+    if !matches!(event.status, TrainStatus::Station | TrainStatus::Departed) {
+        return None;
+    }
+
+    event
+        .arrival_delay_minutes
+        .or(event.departure_delay_minutes)
+}
+
+fn groups<K: Ord>(samples: BTreeMap<K, Vec<i64>>, threshold_minutes: i64) -> Vec<Group<K>> {
+    samples
+        .into_iter()
+        .filter_map(|(key, delays)| {
+            Summary::new(delays, threshold_minutes).map(|summary| Group { key, summary })
+        })
+        .collect()
+}
+
+impl Report {
+    /// The threshold Amtrak uses to report on-time performance
+    pub const DEFAULT_THRESHOLD_MINUTES: i64 = 15;
+
+    /// Computes the report of the provided runs
+    pub fn from_runs(runs: &[Run], threshold_minutes: i64) -> Self {
+        let mut overall = Vec::new();
+        let mut by_route: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+        let mut by_train_num: BTreeMap<u32, Vec<i64>> = BTreeMap::new();
+        let mut by_station: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+        let mut by_weekday: BTreeMap<u32, Vec<i64>> = BTreeMap::new();
+        let mut segments: BTreeMap<(&str, &str, &str), Vec<i64>> = BTreeMap::new();
+
+        for run in runs {
+            let weekday = run
+                .train
+                .stations
+                .first()
+                .map_or(run.departure.weekday(), |station| {
+                    station.schedule_departure.weekday()
+                });
+            let mut previous: Option<(&str, i64)> = None;
+
+            for event in &run.stop_events {
+                let Some(delay) = reached_delay(event) else {
+                    continue;
+                };
+
+                overall.push(delay);
+                by_route
+                    .entry(run.route_name.clone())
+                    .or_default()
+                    .push(delay);
+                by_train_num.entry(run.train_num).or_default().push(delay);
+                by_station
+                    .entry(event.station_code.clone())
+                    .or_default()
+                    .push(delay);
+                by_weekday
+                    .entry(weekday.num_days_from_monday())
+                    .or_default()
+                    .push(delay);
+
+                if let Some((from, previous_delay)) = previous {
+                    segments
+                        .entry((&run.route_name, from, &event.station_code))
+                        .or_default()
+                        .push(delay - previous_delay);
+                }
+                previous = Some((&event.station_code, delay));
+            }
+        }
+
+        let mut segments: Vec<_> = segments
+            .into_iter()
+            .map(|((route_name, from, to), deltas)| SegmentDelay {
+                route_name: route_name.to_string(),
+                from_station: from.to_string(),
+                to_station: to.to_string(),
+                samples: deltas.len(),
+                mean_delay_introduced_minutes: deltas.iter().sum::<i64>() as f64
+                    / deltas.len() as f64,
+            })
+            .collect();
+        segments.sort_by(|a, b| {
+            b.mean_delay_introduced_minutes
+                .total_cmp(&a.mean_delay_introduced_minutes)
+        });
+
+        Self {
+            threshold_minutes,
+            overall: Summary::new(overall, threshold_minutes),
+            by_route: groups(by_route, threshold_minutes),
+            by_train_num: groups(by_train_num, threshold_minutes),
+            by_station: groups(by_station, threshold_minutes),
+            by_weekday: groups(by_weekday, threshold_minutes)
+                .into_iter()
+                .map(|group| Group {
+                    key: Weekday::try_from(group.key as u8).unwrap(),
+                    summary: group.summary,
+                })
+                .collect(),
+            segments,
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! # Amtrak departure board
+//!
+//! Displays the upcoming trains of a station in the terminal, similar to the
+//! departure boards found in the stations themselves. The board refreshes
+//! itself periodically and colors each train based on its delay.
+//!
+//! Key bindings:
+//!
+//! * `s` - Switch to another station (type the code and press enter)
+//! * `r` - Refresh the board now
+//! * `q` or `Esc` - Quit
+use std::{
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use amtrak_api::{
+    board::{Board, DelaySeverity},
+    errors::Error,
+    Client,
+};
+use chrono::{DateTime, Local};
+use clap::Parser;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table},
+    DefaultTerminal, Frame,
+};
+
+/// Display the live departure board of an Amtrak station
+#[derive(Debug, Parser)]
+#[command(name = "amtrak-board", version)]
+struct Cli {
+    /// The station code to display (for example "PHL")
+    station: String,
+
+    /// Base URL of the API, useful to point at a mirror
+    #[arg(long, env = "AMTRAK_API_BASE_URL")]
+    base_url: Option<String>,
+
+    /// Number of seconds between automatic refreshes
+    #[arg(long, default_value_t = 60)]
+    interval: u64,
+}
+
+/// The result of fetching the board of a station
+type BoardUpdate = (String, Result<Board, Error>);
+
+/// State of the application between two frames
+struct App {
+    client: Client,
+    runtime: tokio::runtime::Runtime,
+    updates: (mpsc::Sender<BoardUpdate>, mpsc::Receiver<BoardUpdate>),
+    interval: Duration,
+    station_code: String,
+    board: Option<Board>,
+    error: Option<String>,
+    loading: bool,
+    last_refresh: Option<(Instant, DateTime<Local>)>,
+    input: Option<String>,
+}
+
+impl App {
+    /// Fetches the board of the current station in the background
+    fn refresh(&mut self) {
+        let client = self.client.clone();
+        let station_code = self.station_code.clone();
+        let sender = self.updates.0.clone();
+
+        self.loading = true;
+        self.runtime.spawn(async move {
+            let result = client.board(&station_code).await;
+            // The receiver only goes away once the application exits
+            let _ = sender.send((station_code, result));
+        });
+    }
+
+    /// Applies the boards fetched in the background, ignoring the ones of a
+    /// station the user already switched away from.
+    fn receive_updates(&mut self) {
+        while let Ok((station_code, result)) = self.updates.1.try_recv() {
+            if station_code != self.station_code {
+                continue;
+            }
+
+            self.loading = false;
+            self.last_refresh = Some((Instant::now(), Local::now()));
+            match result {
+                Ok(board) => {
+                    self.board = Some(board);
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    fn refresh_due(&self) -> bool {
+        !self.loading
+            && self
+                .last_refresh
+                .is_none_or(|(refreshed, _)| refreshed.elapsed() >= self.interval)
+    }
+
+    fn switch_station(&mut self, station_code: String) {
+        self.station_code = station_code;
+        self.board = None;
+        self.error = None;
+        self.last_refresh = None;
+        self.refresh();
+    }
+
+    /// Handles a key press, returns `false` when the application should exit
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        match (&mut self.input, code) {
+            (Some(input), KeyCode::Char(c)) if c.is_ascii_alphanumeric() => {
+                input.push(c.to_ascii_uppercase())
+            }
+            (Some(input), KeyCode::Backspace) => {
+                input.pop();
+            }
+            (Some(input), KeyCode::Enter) => {
+                let station_code = std::mem::take(input);
+                self.input = None;
+                if !station_code.is_empty() {
+                    self.switch_station(station_code);
+                }
+            }
+            (Some(_), KeyCode::Esc) => self.input = None,
+            (Some(_), _) => {}
+            (None, KeyCode::Char('q') | KeyCode::Esc) => return false,
+            (None, KeyCode::Char('r')) => self.refresh(),
+            (None, KeyCode::Char('s')) => self.input = Some(String::new()),
+            (None, _) => {}
+        }
+
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let status = match (&self.error, self.loading, &self.last_refresh) {
+            (Some(error), _, _) => format!("Error: {}", error),
+            (None, true, _) => "Refreshing...".to_string(),
+            (None, false, Some((_, refreshed))) => {
+                format!("Updated at {}", refreshed.format("%H:%M:%S"))
+            }
+            (None, false, None) => String::new(),
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(format!(
+                "Departures from {}  {}",
+                self.station_code, status
+            )))
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+            header,
+        );
+
+        let rows = self
+            .board
+            .iter()
+            .flat_map(|board| &board.entries)
+            .map(|entry| {
+                let color = match entry.severity() {
+                    DelaySeverity::OnTime => Color::Green,
+                    DelaySeverity::Minor => Color::Yellow,
+                    DelaySeverity::Major => Color::Red,
+                    DelaySeverity::Unknown => Color::Gray,
+                };
+
+                Row::new(vec![
+                    entry.train_id.clone(),
+                    entry.route_name.clone(),
+                    entry.destination_name.clone(),
+                    entry.scheduled_departure.format("%H:%M").to_string(),
+                    entry.expected_departure().format("%H:%M").to_string(),
+                    format!("{:?}", entry.status),
+                    entry.arrival_comment.clone(),
+                ])
+                .style(Style::default().fg(color))
+            });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Percentage(25),
+                Constraint::Percentage(30),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Length(8),
+                Constraint::Min(16),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "Train",
+                "Route",
+                "Destination",
+                "Scheduled",
+                "Expected",
+                "Status",
+                "Comment",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered());
+        frame.render_widget(table, body);
+
+        let help = match &self.input {
+            Some(input) => format!("Station code: {}_  (enter to switch, esc to cancel)", input),
+            None => "s: switch station  r: refresh  q: quit".to_string(),
+        };
+        frame.render_widget(Paragraph::new(help), footer);
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> std::io::Result<()> {
+    loop {
+        app.receive_updates();
+        if app.refresh_due() {
+            app.refresh();
+        }
+
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !app.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let cli = Cli::parse();
+
+    let mut app = App {
+        client: match &cli.base_url {
+            Some(base_url) => Client::with_base_url(base_url),
+            None => Client::new(),
+        },
+        runtime: tokio::runtime::Runtime::new()?,
+        updates: mpsc::channel(),
+        interval: Duration::from_secs(cli.interval.max(1)),
+        station_code: cli.station,
+        board: None,
+        error: None,
+        loading: false,
+        last_refresh: None,
+        input: None,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+
+    result
+}
@@ -0,0 +1,51 @@
+//! # Amtrak server
+//!
+//! Polls the Amtrak API and serves the cached trains, departure boards and
+//! GeoJSON over HTTP. See the [`server`] module for the list of endpoints.
+//!
+//! [`server`]: amtrak_api::server
+use std::time::Duration;
+
+use amtrak_api::{server::Server, Client};
+use clap::Parser;
+
+/// Serve normalized Amtrak train data over HTTP
+#[derive(Debug, Parser)]
+#[command(name = "amtrak-server", version)]
+struct Cli {
+    /// Address the server listens on
+    #[arg(long, env = "AMTRAK_SERVER_LISTEN", default_value = "127.0.0.1:8080")]
+    listen: String,
+
+    /// Path every endpoint is served under (for example "/amtrak")
+    #[arg(long, env = "AMTRAK_SERVER_BASE_PATH", default_value = "")]
+    base_path: String,
+
+    /// Base URL of the API, useful to point at a mirror
+    #[arg(long, env = "AMTRAK_API_BASE_URL")]
+    base_url: Option<String>,
+
+    /// Number of seconds between two polls of the API
+    #[arg(long, default_value_t = 30)]
+    interval: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let client = match &cli.base_url {
+        Some(base_url) => Client::with_base_url(base_url),
+        None => Client::new(),
+    };
+
+    let listener = tokio::net::TcpListener::bind(&cli.listen).await?;
+    eprintln!("amtrak-server: listening on {}", listener.local_addr()?);
+
+    Server::new(client)
+        .with_interval(Duration::from_secs(cli.interval.max(1)))
+        .with_base_path(&cli.base_path)
+        .serve(listener)
+        .await?;
+
+    Ok(())
+}
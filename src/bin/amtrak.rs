@@ -0,0 +1,664 @@
+//! # Amtrak command line interface
+//!
+//! Queries the Amtrak API for trains and stations from the terminal. Every
+//! command can print its result as a table (the default), JSON or CSV.
+//!
+//! The process exits with one of the following codes:
+//!
+//! * `0` - The command succeeded
+//! * `1` - Any other error
+//! * `2` - The command line arguments are invalid
+//! * `3` - The requested train or station is not in the Amtrak network
+//! * `4` - The request to the API failed
+//! * `5` - The API response could not be deserialized
+//! * `6` - The API returned an error response
+//! * `7` - The output could not be written
+//! * `8` - The history database could not be read
+//! * `9` - The output could not be encoded as CSV
+//! * `10` - The GTFS archive could not be read
+//! * `11` - The configuration could not be parsed or is invalid
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Write},
+    process::ExitCode,
+    time::Duration,
+};
+
+use amtrak_api::{
+    address::StateOrProvince,
+    errors::Error,
+    export,
+    responses::{Station, Train, TrainResponse},
+    Client,
+};
+#[cfg(feature = "sqlite")]
+use amtrak_api::{analytics, storage};
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+/// Query trains and stations in the Amtrak network
+#[derive(Debug, Parser)]
+#[command(name = "amtrak", version, after_help = EXIT_CODES_HELP)]
+struct Cli {
+    /// Base URL of the API, useful to point at a mirror
+    #[arg(long, global = true, env = "AMTRAK_API_BASE_URL")]
+    base_url: Option<String>,
+
+    /// Format used to print the result
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  any other error
+  2  invalid arguments
+  3  train or station not found
+  4  request to the API failed
+  5  API response could not be deserialized
+  6  API returned an error response
+  7  output could not be written
+  8  history database could not be read
+  9  output could not be encoded as CSV
+  10 GTFS archive could not be read
+  11 configuration could not be parsed or is invalid";
+
+#[derive(Debug, Copy, Clone, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List all trains being tracked
+    Trains {
+        /// Only list trains running on this route (for example "Keystone")
+        #[arg(long)]
+        route: Option<String>,
+    },
+
+    /// Show a single train and its stops
+    Train {
+        /// The train id (for example "612-5") or train number (for example
+        /// "612")
+        id: String,
+    },
+
+    /// List all stations in the network
+    Stations {
+        /// Only list stations in this state or province (for example "PA" or
+        /// "Pennsylvania")
+        #[arg(long)]
+        state: Option<StateOrProvince>,
+    },
+
+    /// Show a single station
+    Station {
+        /// The station code (for example "PHL")
+        code: String,
+    },
+
+    /// Show the upcoming departures of a station
+    Board {
+        /// The station code (for example "PHL")
+        code: String,
+    },
+
+    /// Poll the API and print trains every time they are updated
+    Watch {
+        /// Only watch this train id or train number
+        id: Option<String>,
+
+        /// Number of seconds to wait between polls
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+
+    /// Report the on-time performance of the runs stored in a history
+    /// database
+    #[cfg(feature = "sqlite")]
+    Report {
+        /// Path of the SQLite history database
+        #[arg(long, env = "AMTRAK_DATABASE")]
+        database: std::path::PathBuf,
+
+        /// Number of days of history to report on
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+
+        /// Trains arriving at most this many minutes late are on time
+        #[arg(long, default_value_t = analytics::Report::DEFAULT_THRESHOLD_MINUTES)]
+        threshold: i64,
+
+        /// Only report on runs of this route (for example "Keystone")
+        #[arg(long)]
+        route: Option<String>,
+    },
+}
+
+/// Errors that terminate the command line interface
+#[derive(Debug)]
+enum CliError {
+    NotFound(String),
+    Api(Error),
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            Self::NotFound(_) => 3,
+            Self::Api(Error::RequestFailed(e)) if e.is_decode() => 5,
+            Self::Api(Error::RequestFailed(_)) => 4,
+            // Serializing the output to a closed pipe is a write failure
+            Self::Api(Error::DeserializeFailed(e)) if e.is_io() => 7,
+            Self::Api(Error::DeserializeFailed(_)) => 5,
+            Self::Api(Error::ApiErrorResponse(_)) => 6,
+            Self::Api(Error::IoFailed(_)) => 7,
+            #[cfg(feature = "sqlite")]
+            Self::Api(Error::SqliteFailed(_)) => 8,
+            Self::Api(Error::CsvFailed(e)) if e.is_io_error() => 7,
+            Self::Api(Error::CsvFailed(_)) => 9,
+            #[cfg(feature = "gtfs")]
+            Self::Api(Error::ZipFailed(_)) => 10,
+            #[cfg(feature = "notify")]
+            Self::Api(Error::ConfigFailed(_) | Error::InvalidConfig(_)) => 11,
+            Self::Api(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound(e) => write!(f, "{}", e),
+            Self::Api(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: Into<Error>> From<E> for CliError {
+    fn from(error: E) -> Self {
+        Self::Api(error.into())
+    }
+}
+
+type Result<T> = std::result::Result<T, CliError>;
+
+/// A station flattened into a single CSV record
+#[derive(Debug, Serialize)]
+struct StationRecord<'a> {
+    code: &'a str,
+    name: &'a str,
+    address1: &'a str,
+    address2: &'a str,
+    city: &'a str,
+    state: &'a str,
+    zip: &'a str,
+    tz: &'a str,
+    lat: f64,
+    lon: f64,
+    trains: String,
+}
+
+impl<'a> From<&'a Station> for StationRecord<'a> {
+    fn from(station: &'a Station) -> Self {
+        Self {
+            code: &station.code,
+            name: &station.name,
+            address1: station.address1.trim(),
+            address2: station.address2.trim(),
+            city: &station.city,
+            state: &station.state,
+            zip: &station.zip,
+            tz: &station.tz,
+            lat: station.lat,
+            lon: station.lon,
+            trains: station.trains.join(" "),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = match &cli.base_url {
+        Some(base_url) => Client::with_base_url(base_url),
+        None => Client::new(),
+    };
+
+    match run(&cli, &client).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("amtrak: {}", e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run(cli: &Cli, client: &Client) -> Result<()> {
+    match &cli.command {
+        Command::Trains { route } => {
+            let response = client.trains().await?;
+            let mut trains = sorted_trains(&response);
+            if let Some(route) = route {
+                trains.retain(|train| train.route_name.eq_ignore_ascii_case(route));
+            }
+
+            print_trains(cli.format, &trains)
+        }
+        Command::Train { id } => {
+            let response = client.train(id).await?;
+            let trains = sorted_trains(&response);
+            if trains.is_empty() {
+                return Err(CliError::NotFound(format!(
+                    "Train \"{}\" is not currently in the Amtrak network",
+                    id
+                )));
+            }
+
+            print_train_stops(cli.format, &trains)
+        }
+        Command::Stations { state } => {
+            let response = client.stations().await?;
+            let mut stations: Vec<_> = match state {
+                Some(state) => response.in_state(*state).collect(),
+                None => response.0.values().collect(),
+            };
+            stations.sort_by(|a, b| a.code.cmp(&b.code));
+
+            print_stations(cli.format, &stations)
+        }
+        Command::Station { code } => {
+            let response = client.station(code).await?;
+            let stations: Vec<_> = response.0.values().collect();
+            if stations.is_empty() {
+                return Err(CliError::NotFound(format!(
+                    "Station \"{}\" is not in the Amtrak network",
+                    code
+                )));
+            }
+
+            print_stations(cli.format, &stations)
+        }
+        Command::Board { code } => {
+            let board = client.board(code).await?;
+            for train_id in &board.unavailable {
+                eprintln!("amtrak: Train \"{}\" could not be fetched", train_id);
+            }
+
+            match cli.format {
+                Format::Table => print_table(
+                    &[
+                        "TRAIN",
+                        "ROUTE",
+                        "DESTINATION",
+                        "SCHEDULED",
+                        "EXPECTED",
+                        "STATUS",
+                        "COMMENT",
+                    ],
+                    board
+                        .entries
+                        .iter()
+                        .map(|entry| {
+                            vec![
+                                entry.train_id.clone(),
+                                entry.route_name.clone(),
+                                entry.destination_name.clone(),
+                                time(entry.scheduled_departure),
+                                time(entry.expected_departure()),
+                                format!("{:?}", entry.status),
+                                entry.arrival_comment.clone(),
+                            ]
+                        })
+                        .collect(),
+                )?,
+                Format::Json => print_json(&board)?,
+                Format::Csv => print_csv(&board.entries)?,
+            }
+
+            Ok(())
+        }
+        Command::Watch { id, interval } => {
+            watch(cli.format, client, id.as_deref(), *interval).await
+        }
+        #[cfg(feature = "sqlite")]
+        Command::Report {
+            database,
+            days,
+            threshold,
+            route,
+        } => {
+            let store = storage::Store::open_existing(database)?;
+            let mut runs = store.all_runs(chrono::Utc::now() - chrono::Duration::days(*days))?;
+            if let Some(route) = route {
+                runs.retain(|run| run.route_name.eq_ignore_ascii_case(route));
+            }
+
+            print_report(cli.format, &analytics::Report::from_runs(&runs, *threshold))
+        }
+    }
+}
+
+/// Returns every train of the response ordered by train id
+fn sorted_trains(response: &TrainResponse) -> Vec<&Train> {
+    let mut trains: Vec<_> = response.iter_trains().collect();
+    trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+    trains
+}
+
+/// Formats a timestamp using the local time of the station
+fn time(timestamp: DateTime<FixedOffset>) -> String {
+    timestamp.format("%H:%M").to_string()
+}
+
+fn train_row(train: &Train) -> Vec<String> {
+    let next_stop = train
+        .remaining_stations()
+        .next()
+        .map(|station| {
+            let expected = station.arrival.unwrap_or(station.schedule_arrival);
+            format!("{} {}", station.code, time(expected))
+        })
+        .unwrap_or_default();
+
+    vec![
+        train.train_id.clone(),
+        train.route_name.clone(),
+        train.origin_code.clone(),
+        train.destination_code.clone(),
+        format!("{:?}", train.train_state),
+        train.train_timely.clone(),
+        next_stop,
+    ]
+}
+
+const TRAIN_HEADERS: [&str; 7] = [
+    "TRAIN",
+    "ROUTE",
+    "FROM",
+    "TO",
+    "STATE",
+    "TIMELY",
+    "NEXT STOP",
+];
+
+/// Widths of the [`TRAIN_HEADERS`] columns when the rows are printed as they
+/// arrive, longer cells push the following columns to the right.
+const WATCH_WIDTHS: [usize; 7] = [8, 20, 4, 4, 12, 16, 9];
+
+fn print_trains(format: Format, trains: &[&Train]) -> Result<()> {
+    match format {
+        Format::Table => print_table(
+            &TRAIN_HEADERS,
+            trains.iter().map(|train| train_row(train)).collect(),
+        )?,
+        Format::Json => print_json(&trains)?,
+        Format::Csv => export::write_csv(
+            trains.iter().flat_map(|train| train.stop_events()),
+            io::stdout(),
+        )?,
+    }
+
+    Ok(())
+}
+
+fn print_train_stops(format: Format, trains: &[&Train]) -> Result<()> {
+    if !matches!(format, Format::Table) {
+        return print_trains(format, trains);
+    }
+
+    for train in trains {
+        print_table(&TRAIN_HEADERS, vec![train_row(train)])?;
+        writeln!(io::stdout().lock())?;
+        print_table(
+            &[
+                "CODE", "STATION", "SCH ARR", "SCH DEP", "ARR", "DEP", "STATUS", "COMMENT",
+            ],
+            train
+                .stations
+                .iter()
+                .map(|station| {
+                    vec![
+                        station.code.clone(),
+                        station.name.clone(),
+                        time(station.schedule_arrival),
+                        time(station.schedule_departure),
+                        station.arrival.map(time).unwrap_or_default(),
+                        station.departure.map(time).unwrap_or_default(),
+                        format!("{:?}", station.status),
+                        station.arrival_comment.clone(),
+                    ]
+                })
+                .collect(),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn print_stations(format: Format, stations: &[&Station]) -> Result<()> {
+    match format {
+        Format::Table => print_table(
+            &["CODE", "NAME", "CITY", "STATE", "TRAINS"],
+            stations
+                .iter()
+                .map(|station| {
+                    vec![
+                        station.code.clone(),
+                        station.name.clone(),
+                        station.city.clone(),
+                        station.state.clone(),
+                        station.trains.len().to_string(),
+                    ]
+                })
+                .collect(),
+        )?,
+        Format::Json => print_json(&stations)?,
+        Format::Csv => print_csv(stations.iter().map(|station| StationRecord::from(*station)))?,
+    }
+
+    Ok(())
+}
+
+/// A group of an on-time performance report flattened into a single record
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Serialize)]
+struct ReportRecord<'a> {
+    group: &'a str,
+    key: String,
+    samples: usize,
+    on_time: usize,
+    on_time_performance: f64,
+    mean_delay_minutes: f64,
+    p50_delay_minutes: i64,
+    p90_delay_minutes: i64,
+    p95_delay_minutes: i64,
+    max_delay_minutes: i64,
+}
+
+#[cfg(feature = "sqlite")]
+impl<'a> ReportRecord<'a> {
+    fn new(group: &'a str, key: String, summary: &analytics::Summary) -> Self {
+        Self {
+            group,
+            key,
+            samples: summary.samples,
+            on_time: summary.on_time,
+            on_time_performance: summary.on_time_performance,
+            mean_delay_minutes: summary.mean_delay_minutes,
+            p50_delay_minutes: summary.p50_delay_minutes,
+            p90_delay_minutes: summary.p90_delay_minutes,
+            p95_delay_minutes: summary.p95_delay_minutes,
+            max_delay_minutes: summary.max_delay_minutes,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn print_report(format: Format, report: &analytics::Report) -> Result<()> {
+    fn records<'a, K: ToString>(
+        group: &'a str,
+        groups: &'a [analytics::Group<K>],
+    ) -> impl Iterator<Item = ReportRecord<'a>> {
+        groups
+            .iter()
+            .map(move |g| ReportRecord::new(group, g.key.to_string(), &g.summary))
+    }
+
+    let records: Vec<_> = report
+        .overall
+        .iter()
+        .map(|summary| ReportRecord::new("overall", "all".to_string(), summary))
+        .chain(records("route", &report.by_route))
+        .chain(records("train", &report.by_train_num))
+        .chain(records("station", &report.by_station))
+        .chain(records("weekday", &report.by_weekday))
+        .collect();
+
+    match format {
+        Format::Table => {
+            print_table(
+                &[
+                    "GROUP", "KEY", "SAMPLES", "ON TIME", "OTP", "MEAN", "P50", "P90", "P95", "MAX",
+                ],
+                records
+                    .iter()
+                    .map(|record| {
+                        vec![
+                            record.group.to_string(),
+                            record.key.clone(),
+                            record.samples.to_string(),
+                            record.on_time.to_string(),
+                            format!("{:.1}%", record.on_time_performance * 100.0),
+                            format!("{:.1}", record.mean_delay_minutes),
+                            record.p50_delay_minutes.to_string(),
+                            record.p90_delay_minutes.to_string(),
+                            record.p95_delay_minutes.to_string(),
+                            record.max_delay_minutes.to_string(),
+                        ]
+                    })
+                    .collect(),
+            )?;
+            writeln!(io::stdout().lock())?;
+            print_table(
+                &["ROUTE", "FROM", "TO", "SAMPLES", "DELAY INTRODUCED"],
+                report
+                    .segments
+                    .iter()
+                    .map(|segment| {
+                        vec![
+                            segment.route_name.clone(),
+                            segment.from_station.clone(),
+                            segment.to_station.clone(),
+                            segment.samples.to_string(),
+                            format!("{:.1}", segment.mean_delay_introduced_minutes),
+                        ]
+                    })
+                    .collect(),
+            )?;
+        }
+        Format::Json => print_json(report)?,
+        Format::Csv => print_csv(&records)?,
+    }
+
+    Ok(())
+}
+
+/// Polls the API every `interval` seconds and prints every train whose
+/// `updatedAt` field changed since the previous poll.
+///
+/// A failed poll is reported on stderr and retried on the next tick, only
+/// failing to write the output ends the watch.
+async fn watch(format: Format, client: &Client, id: Option<&str>, interval: u64) -> Result<()> {
+    let mut last_updated = HashMap::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    let mut csv = csv::Writer::from_writer(io::stdout());
+
+    if matches!(format, Format::Table) {
+        print_row(&TRAIN_HEADERS, &WATCH_WIDTHS)?;
+    }
+
+    loop {
+        ticker.tick().await;
+
+        let response = match id {
+            Some(id) => client.train(id).await,
+            None => client.trains().await,
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("amtrak: {}", e);
+                continue;
+            }
+        };
+
+        for train in sorted_trains(&response) {
+            if last_updated.insert(train.train_id.clone(), train.updated_at)
+                == Some(train.updated_at)
+            {
+                continue;
+            }
+
+            match format {
+                Format::Table => print_row(&train_row(train), &WATCH_WIDTHS)?,
+                Format::Json => writeln!(io::stdout().lock(), "{}", serde_json::to_string(train)?)?,
+                Format::Csv => {
+                    for event in train.stop_events() {
+                        csv.serialize(event)?;
+                    }
+                    csv.flush()?;
+                }
+            }
+        }
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut stdout, value)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn print_csv<T: Serialize, I: IntoIterator<Item = T>>(records: I) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) -> Result<()> {
+    let mut widths: Vec<_> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    print_row(headers, &widths)?;
+    for row in &rows {
+        print_row(row, &widths)?;
+    }
+    Ok(())
+}
+
+fn print_row<S: AsRef<str>>(cells: &[S], widths: &[usize]) -> Result<()> {
+    let line: Vec<_> = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell.as_ref(), width = width))
+        .collect();
+    writeln!(io::stdout().lock(), "{}", line.join("  ").trim_end())?;
+    Ok(())
+}
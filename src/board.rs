@@ -0,0 +1,181 @@
+//! Station departure boards
+//!
+//! A [`Board`] lists the trains that have yet to depart from a station,
+//! ordered by their expected departure, similar to the boards displayed in the
+//! stations themselves.
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::Serialize;
+
+use crate::responses::{Train, TrainResponse, TrainStation, TrainStatus};
+
+/// How severe the delay of a train is
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq)]
+pub enum DelaySeverity {
+    /// The train is early or at most 5 minutes late
+    OnTime,
+
+    /// The train is more than 5 minutes but at most 15 minutes late
+    Minor,
+
+    /// The train is more than 15 minutes late
+    Major,
+
+    /// The delay of the train is not known
+    Unknown,
+}
+
+impl DelaySeverity {
+    /// Classifies the provided delay, a negative delay means the train is
+    /// early.
+    pub fn from_delay(delay: Option<Duration>) -> Self {
+        match delay.map(|delay| delay.num_minutes()) {
+            None => Self::Unknown,
+            Some(minutes) if minutes <= 5 => Self::OnTime,
+            Some(minutes) if minutes <= 15 => Self::Minor,
+            Some(_) => Self::Major,
+        }
+    }
+}
+
+/// A single train listed on a [`Board`]
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BoardEntry {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// The name of the station the train originated from
+    pub origin_name: String,
+
+    /// The name of the final destination of the train
+    pub destination_name: String,
+
+    /// The scheduled arrival of the train at the station
+    pub scheduled_arrival: DateTime<FixedOffset>,
+
+    /// The scheduled departure of the train from the station
+    pub scheduled_departure: DateTime<FixedOffset>,
+
+    /// The estimated arrival of the train at the station
+    pub estimated_arrival: Option<DateTime<FixedOffset>>,
+
+    /// The estimated departure of the train from the station
+    pub estimated_departure: Option<DateTime<FixedOffset>>,
+
+    /// The human readable arrival comment (for example `19 Minutes Late`)
+    pub arrival_comment: String,
+
+    /// The arrival delay in minutes parsed from the [`arrival_comment`], a
+    /// negative value means the train is early.
+    ///
+    /// [`arrival_comment`]: Self::arrival_comment
+    pub delay_minutes: Option<i64>,
+
+    /// Whether the train is still enroute or already at the station
+    pub status: TrainStatus,
+}
+
+impl BoardEntry {
+    fn new(train: &Train, stop: &TrainStation) -> Self {
+        Self {
+            train_id: train.train_id.clone(),
+            train_num: train.train_num,
+            route_name: train.route_name.clone(),
+            origin_name: train.origin_name.clone(),
+            destination_name: train.destination_name.clone(),
+            scheduled_arrival: stop.schedule_arrival,
+            scheduled_departure: stop.schedule_departure,
+            estimated_arrival: stop.arrival,
+            estimated_departure: stop.departure,
+            arrival_comment: stop.arrival_comment.clone(),
+            delay_minutes: stop.arrival_delay().map(|delay| delay.num_minutes()),
+            status: stop.status,
+        }
+    }
+
+    /// Returns how severe the arrival delay of this train is
+    pub fn severity(&self) -> DelaySeverity {
+        DelaySeverity::from_delay(self.delay_minutes.map(Duration::minutes))
+    }
+
+    /// Returns the best known departure time, the estimate if there is one
+    /// otherwise the schedule.
+    pub fn expected_departure(&self) -> DateTime<FixedOffset> {
+        self.estimated_departure.unwrap_or(self.scheduled_departure)
+    }
+}
+
+/// The upcoming departures of a station
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Board {
+    /// The code of the station this board belongs to, in uppercase
+    pub station_code: String,
+
+    /// The trains that have not yet departed the station, ordered by their
+    /// [`expected_departure`].
+    ///
+    /// [`expected_departure`]: BoardEntry::expected_departure
+    pub entries: Vec<BoardEntry>,
+
+    /// The [`train_id`] of the trains scheduled for the station that could
+    /// not be fetched, their departures are missing from [`entries`].
+    ///
+    /// [`train_id`]: Train::train_id
+    /// [`entries`]: Self::entries
+    pub unavailable: Vec<String>,
+}
+
+impl Board {
+    /// Builds the board of the station with the provided code from a list of
+    /// trains. Trains that do not stop at the station or that have already
+    /// departed from it are left out.
+    ///
+    /// The station code is matched ignoring case.
+    pub fn from_trains<'a, I>(station_code: &str, trains: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Train>,
+    {
+        let mut entries: Vec<_> = trains
+            .into_iter()
+            .filter_map(|train| {
+                train
+                    .stations
+                    .iter()
+                    .find(|station| station.code.eq_ignore_ascii_case(station_code))
+                    .filter(|station| station.status != TrainStatus::Departed)
+                    .map(|station| BoardEntry::new(train, station))
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            a.expected_departure()
+                .cmp(&b.expected_departure())
+                .then_with(|| a.train_id.cmp(&b.train_id))
+        });
+
+        Self {
+            station_code: station_code.to_ascii_uppercase(),
+            entries,
+            unavailable: Vec::new(),
+        }
+    }
+}
+
+impl TrainResponse {
+    /// Returns the departure board of the station with the provided code
+    /// built from the trains in this response.
+    pub fn board(&self, station_code: &str) -> Board {
+        Board::from_trains(station_code, self.iter_trains())
+    }
+}
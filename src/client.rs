@@ -3,7 +3,11 @@
 //! The client allows the user to call the various different endpoints provided
 //! by the API.
 
-use crate::{errors, responses};
+use std::{sync::Arc, time::Instant};
+
+use serde::de::DeserializeOwned;
+
+use crate::{board, errors, metrics::ClientMetrics, responses};
 
 /// Default endpoint for Amtrak API
 const BASE_API_URL: &str = "https://api-v3.amtraker.com/v3";
@@ -17,6 +21,7 @@ pub type Result<T> = std::result::Result<T, errors::Error>;
 #[derive(Debug, Clone)]
 pub struct Client {
     base_url: String,
+    metrics: Arc<ClientMetrics>,
 }
 
 impl Default for Client {
@@ -40,9 +45,7 @@ impl Client {
     /// }
     /// ```
     pub fn new() -> Self {
-        Self {
-            base_url: BASE_API_URL.to_string(),
-        }
+        Self::with_base_url(BASE_API_URL)
     }
 
     /// Creates a new instance with the provided Amtrak endpoint
@@ -69,9 +72,57 @@ impl Client {
     pub fn with_base_url(base_url: &str) -> Self {
         Self {
             base_url: base_url.to_string(),
+            metrics: Arc::default(),
         }
     }
 
+    /// Returns the metrics recorded by this client and its clones
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Client::new();
+    ///     client.trains().await?;
+    ///
+    ///     print!("{}", client.metrics());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn metrics(&self) -> &Arc<ClientMetrics> {
+        &self.metrics
+    }
+
+    /// Calls into the provided path of the API, recording the request under
+    /// the `endpoint` label of the client metrics.
+    async fn get<T: DeserializeOwned>(&self, endpoint: &'static str, path: &str) -> Result<T> {
+        let start = Instant::now();
+        let result = self.fetch(path).await;
+
+        self.metrics.record_request(endpoint, start.elapsed());
+        if let Err(e) = &result {
+            self.metrics.record_error(e);
+        }
+
+        result
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+
+        let response = reqwest::Client::new()
+            .get(url)
+            .send()
+            .await?
+            .json::<T>()
+            .await?;
+
+        Ok(response)
+    }
+
     /// Returns all trains being tracked by Amtrak
     ///
     /// This function calls into the `/trains` endpoint.
@@ -82,7 +133,7 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use amtrak_api::{responses::TrainStatus, Client};
     /// use chrono::{Local, Utc};
     ///
@@ -92,8 +143,8 @@ impl Client {
     ///         .trains()
     ///         .await?
     ///         .0
-    ///         .into_iter()
-    ///         .flat_map(|(_, trains)| {
+    ///         .into_values()
+    ///         .flat_map(|trains| {
     ///             trains
     ///                 .into_iter()
     ///                 .filter(|train| train.route_name == "Keystone")
@@ -139,16 +190,7 @@ impl Client {
     ///
     /// [`TrainResponse`]: responses::TrainResponse
     pub async fn trains(&self) -> Result<responses::TrainResponse> {
-        let url = format!("{}/trains", self.base_url);
-
-        let response = reqwest::Client::new()
-            .get(url)
-            .send()
-            .await?
-            .json::<responses::TrainResponse>()
-            .await?;
-
-        Ok(response)
+        self.get("trains", "/trains").await
     }
 
     /// Returns the specified train(s) being tracked by Amtrak
@@ -166,7 +208,7 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use amtrak_api::{responses::TrainStatus, Client};
     ///
     /// const TRAIN_ID: &str = "612-5";
@@ -183,7 +225,7 @@ impl Client {
     ///         Some(trains) => match trains.len() {
     ///             1 => {
     ///                 let phl_station = trains
-    ///                     .get(0)
+    ///                     .first()
     ///                     .unwrap()
     ///                     .stations
     ///                     .iter()
@@ -225,16 +267,8 @@ impl Client {
     /// [`train_id`]: responses::Train::train_id
     /// [`train_num`]: responses::Train::train_num
     pub async fn train(&self, train_identifier: &str) -> Result<responses::TrainResponse> {
-        let url = format!("{}/trains/{}", self.base_url, train_identifier);
-
-        let response = reqwest::Client::new()
-            .get(url)
-            .send()
-            .await?
-            .json::<responses::TrainResponse>()
-            .await?;
-
-        Ok(response)
+        self.get("train", &format!("/trains/{}", train_identifier))
+            .await
     }
 
     /// Returns all the stations in the Amtrak network
@@ -247,17 +281,15 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```rust
-    /// use amtrak_api::Client;
+    /// ```rust,no_run
+    /// use amtrak_api::{address::StateOrProvince, Client};
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     Client::new()
     ///         .stations()
     ///         .await?
-    ///         .0
-    ///         .values()
-    ///         .filter(|station| station.state == "PA")
+    ///         .in_state(StateOrProvince::Pennsylvania)
     ///         .for_each(|station| {
     ///             println!("Station \"{}\" is in PA", station.name);
     ///         });
@@ -268,16 +300,7 @@ impl Client {
     ///
     /// [`StationResponse`]: responses::StationResponse
     pub async fn stations(&self) -> Result<responses::StationResponse> {
-        let url = format!("{}/stations", self.base_url);
-
-        let response = reqwest::Client::new()
-            .get(url)
-            .send()
-            .await?
-            .json::<responses::StationResponse>()
-            .await?;
-
-        Ok(response)
+        self.get("stations", "/stations").await
     }
 
     /// Returns the specified station in the Amtrak network
@@ -294,7 +317,7 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use amtrak_api::Client;
     ///
     /// const STATION_CODE: &str = "PHL";
@@ -321,15 +344,76 @@ impl Client {
     /// [`StationResponse`]: responses::StationResponse
     /// [`code`]: responses::TrainStation::code
     pub async fn station(&self, station_code: &str) -> Result<responses::StationResponse> {
-        let url = format!("{}/stations/{}", self.base_url, station_code);
+        self.get("station", &format!("/stations/{}", station_code))
+            .await
+    }
 
-        let response = reqwest::Client::new()
-            .get(url)
-            .send()
-            .await?
-            .json::<responses::StationResponse>()
-            .await?;
+    /// Returns the departure board of the specified station
+    ///
+    /// This function calls into the `/stations/{:station_code}` endpoint and
+    /// then into the `/trains/{:train_id}` endpoint for every train currently
+    /// scheduled for the station. Trains that cannot be fetched are left out
+    /// of the board and listed in its [`unavailable`] field instead of
+    /// failing the whole board.
+    ///
+    /// Check the [`Board`] struct for the data that this function returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `station_code` - The station [`code`] the caller wants the board of.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::Client;
+    ///
+    /// const STATION_CODE: &str = "PHL";
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     Client::new()
+    ///         .board(STATION_CODE)
+    ///         .await?
+    ///         .entries
+    ///         .iter()
+    ///         .for_each(|entry| {
+    ///             println!(
+    ///                 "{} to {} departs at {}",
+    ///                 entry.train_id,
+    ///                 entry.destination_name,
+    ///                 entry.expected_departure().format("%H:%M")
+    ///             );
+    ///         });
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`Board`]: board::Board
+    /// [`unavailable`]: board::Board::unavailable
+    /// [`code`]: responses::Station::code
+    pub async fn board(&self, station_code: &str) -> Result<board::Board> {
+        let mut trains = Vec::new();
+        let mut unavailable = Vec::new();
 
-        Ok(response)
+        for station in self.station(station_code).await?.0.into_values() {
+            for train_id in station.trains {
+                match self.train(&train_id).await {
+                    Ok(response) => trains.extend(
+                        response
+                            .0
+                            .into_values()
+                            .flatten()
+                            .filter(|train| train.train_id == train_id),
+                    ),
+                    Err(_) => unavailable.push(train_id),
+                }
+            }
+        }
+
+        let mut board = board::Board::from_trains(station_code, &trains);
+        board.unavailable = unavailable;
+
+        Ok(board)
     }
 }
@@ -0,0 +1,277 @@
+//! Connections at risk
+//!
+//! A [`Connection`] is a transfer booked by a traveler from an inbound train
+//! to an outbound train at a given station, for example from the Keystone to
+//! the Northeast Regional at `PHL`. Evaluating it against a
+//! [`TrainResponse`] compares the projected arrival of the inbound train with
+//! the projected departure of the outbound train, both taken from the
+//! estimates of their [`TrainStation`] (or the schedule when there is no
+//! estimate), and classifies the time left to change trains as a
+//! [`ConnectionRisk`].
+//!
+//! A [`ConnectionMonitor`] keeps track of several connections and reports
+//! the ones whose risk changed every time a new snapshot arrives.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{connection::{Connection, ConnectionMonitor}, Client};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Client::new();
+//!     let mut monitor = ConnectionMonitor::new();
+//!     monitor.watch(Connection::new("657-29", "94-29", "PHL"));
+//!
+//!     loop {
+//!         for change in monitor.update(&client.trains().await?) {
+//!             println!("{:?}", change.status.risk);
+//!         }
+//!         tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+//!     }
+//! }
+//! ```
+//!
+//! [`TrainStation`]: crate::responses::TrainStation
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::Serialize;
+
+use crate::{
+    planner::DEFAULT_MIN_CONNECTION_MINUTES,
+    responses::{Train, TrainResponse, TrainStation, TrainStatus},
+};
+
+/// The default margin above the minimum connection time under which a
+/// connection is considered [`Tight`]
+///
+/// [`Tight`]: ConnectionRisk::Tight
+pub const DEFAULT_MARGIN_MINUTES: i64 = 10;
+
+/// How likely the traveler is to miss the outbound train
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionRisk {
+    /// More than the minimum connection time plus the margin is left
+    Safe,
+
+    /// Less than the minimum connection time plus the margin is left
+    Tight,
+
+    /// Less than the minimum connection time is left
+    AtRisk,
+
+    /// The outbound train is projected to leave before the inbound train
+    /// arrives, or already left while the inbound train is still enroute
+    Missed,
+
+    /// One of the trains is missing from the snapshot or does not stop at
+    /// the transfer station
+    Unknown,
+}
+
+/// A transfer from an inbound train to an outbound train
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    /// The [`train_id`] of the train the traveler arrives on
+    ///
+    /// [`train_id`]: Train::train_id
+    pub inbound_train_id: String,
+
+    /// The [`train_id`] of the train the traveler departs on
+    ///
+    /// [`train_id`]: Train::train_id
+    pub outbound_train_id: String,
+
+    /// The code of the station where the traveler changes trains
+    pub station_code: String,
+
+    /// The minimum time needed to change trains
+    pub min_connection: Duration,
+
+    /// The margin above [`min_connection`] under which the connection is
+    /// [`Tight`]
+    ///
+    /// [`min_connection`]: Self::min_connection
+    /// [`Tight`]: ConnectionRisk::Tight
+    pub margin: Duration,
+}
+
+/// The result of evaluating a [`Connection`] against a snapshot
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ConnectionStatus {
+    /// The [`train_id`] of the inbound train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub inbound_train_id: String,
+
+    /// The [`train_id`] of the outbound train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub outbound_train_id: String,
+
+    /// The code of the transfer station
+    pub station_code: String,
+
+    /// The projected arrival of the inbound train at the transfer station
+    pub projected_arrival: Option<DateTime<FixedOffset>>,
+
+    /// The projected departure of the outbound train from the transfer
+    /// station
+    pub projected_departure: Option<DateTime<FixedOffset>>,
+
+    /// The time left to change trains in minutes, a negative value means
+    /// the outbound train leaves first.
+    pub slack_minutes: Option<i64>,
+
+    /// How likely the traveler is to miss the outbound train
+    pub risk: ConnectionRisk,
+}
+
+impl Connection {
+    /// Creates a connection with a minimum connection time of
+    /// [`DEFAULT_MIN_CONNECTION_MINUTES`] and a margin of
+    /// [`DEFAULT_MARGIN_MINUTES`]
+    pub fn new<S: Into<String>>(
+        inbound_train_id: S,
+        outbound_train_id: S,
+        station_code: S,
+    ) -> Self {
+        Self {
+            inbound_train_id: inbound_train_id.into(),
+            outbound_train_id: outbound_train_id.into(),
+            station_code: station_code.into(),
+            min_connection: Duration::minutes(DEFAULT_MIN_CONNECTION_MINUTES),
+            margin: Duration::minutes(DEFAULT_MARGIN_MINUTES),
+        }
+    }
+
+    /// Sets the minimum time needed to change trains
+    pub fn min_connection(mut self, min_connection: Duration) -> Self {
+        self.min_connection = min_connection;
+        self
+    }
+
+    /// Sets the margin above the minimum connection time under which the
+    /// connection is [`Tight`]
+    ///
+    /// [`Tight`]: ConnectionRisk::Tight
+    pub fn margin(mut self, margin: Duration) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Evaluates the connection against the trains of the response
+    pub fn evaluate(&self, response: &TrainResponse) -> ConnectionStatus {
+        let inbound = self.stop(response, &self.inbound_train_id);
+        let outbound = self.stop(response, &self.outbound_train_id);
+
+        let projected_arrival = inbound.map(|stop| stop.arrival.unwrap_or(stop.schedule_arrival));
+        let projected_departure =
+            outbound.map(|stop| stop.departure.unwrap_or(stop.schedule_departure));
+
+        let (slack, risk) = match (
+            inbound.zip(projected_arrival),
+            outbound.zip(projected_departure),
+        ) {
+            (Some((inbound, arrival)), Some((outbound, departure))) => {
+                let slack = departure - arrival;
+                let missed = slack < Duration::zero()
+                    || (outbound.status == TrainStatus::Departed
+                        && inbound.status == TrainStatus::Enroute);
+
+                let risk = if missed {
+                    ConnectionRisk::Missed
+                } else if slack < self.min_connection {
+                    ConnectionRisk::AtRisk
+                } else if slack < self.min_connection + self.margin {
+                    ConnectionRisk::Tight
+                } else {
+                    ConnectionRisk::Safe
+                };
+
+                (Some(slack), risk)
+            }
+            _ => (None, ConnectionRisk::Unknown),
+        };
+
+        ConnectionStatus {
+            inbound_train_id: self.inbound_train_id.clone(),
+            outbound_train_id: self.outbound_train_id.clone(),
+            station_code: self.station_code.clone(),
+            projected_arrival,
+            projected_departure,
+            slack_minutes: slack.map(|slack| slack.num_minutes()),
+            risk,
+        }
+    }
+
+    fn stop<'a>(&self, response: &'a TrainResponse, train_id: &str) -> Option<&'a TrainStation> {
+        response
+            .get_by_id(train_id)
+            .and_then(|train| stop(train, &self.station_code))
+    }
+}
+
+fn stop<'a>(train: &'a Train, station_code: &str) -> Option<&'a TrainStation> {
+    train
+        .stations
+        .iter()
+        .find(|station| station.code.eq_ignore_ascii_case(station_code))
+}
+
+/// A connection whose risk changed between two snapshots
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RiskChange {
+    /// The risk in the previous snapshot, `None` for the first evaluation of
+    /// the connection
+    pub previous_risk: Option<ConnectionRisk>,
+
+    /// The evaluation against the latest snapshot
+    pub status: ConnectionStatus,
+}
+
+/// Re-evaluates connections as new snapshots arrive
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionMonitor {
+    connections: Vec<(Connection, Option<ConnectionRisk>)>,
+}
+
+impl ConnectionMonitor {
+    /// Creates a monitor without any connection
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts watching the connection, its risk is reported by the next
+    /// [`update`]
+    ///
+    /// [`update`]: Self::update
+    pub fn watch(&mut self, connection: Connection) {
+        self.connections.push((connection, None));
+    }
+
+    /// Returns the watched connections
+    pub fn connections(&self) -> impl Iterator<Item = &Connection> {
+        self.connections.iter().map(|(connection, _)| connection)
+    }
+
+    /// Evaluates every connection against the snapshot and returns the ones
+    /// whose risk changed since the previous snapshot
+    pub fn update(&mut self, response: &TrainResponse) -> Vec<RiskChange> {
+        self.connections
+            .iter_mut()
+            .filter_map(|(connection, risk)| {
+                let status = connection.evaluate(response);
+                if *risk == Some(status.risk) {
+                    return None;
+                }
+
+                let previous_risk = risk.replace(status.risk);
+                Some(RiskChange {
+                    previous_risk,
+                    status,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,110 @@
+//! Bundled offline station directory
+//!
+//! The list of stations barely changes, yet [`Client::stations`] needs the
+//! network to return it. With the `bundled-stations` feature a snapshot of
+//! every station (code, name, time zone, position and address) is embedded
+//! into the crate and can be looked up without any request.
+//!
+//! The snapshot lives in `data/stations.json` and is refreshed with the
+//! `refresh_stations` example, either from the API or from a saved
+//! `/stations` response:
+//!
+//! ```sh
+//! cargo run --example refresh_stations -- saved-stations.json
+//! ```
+//!
+//! The [`trains`] of the bundled stations are always empty since they change
+//! all the time, use [`Client::station`] for those.
+//!
+//! # Example
+//!
+//! ```rust
+//! use amtrak_api::directory;
+//!
+//! let philadelphia = directory::station("PHL").unwrap();
+//! assert_eq!(philadelphia.city, "Philadelphia");
+//! ```
+//!
+//! [`Client::stations`]: crate::Client::stations
+//! [`Client::station`]: crate::Client::station
+//! [`trains`]: Station::trains
+
+use std::sync::OnceLock;
+
+use crate::{
+    responses::{Station, StationResponse},
+    search::{words, StationMatch},
+};
+
+const STATIONS_JSON: &str = include_str!("../data/stations.json");
+
+static STATIONS: OnceLock<StationResponse> = OnceLock::new();
+
+/// Returns every bundled station, keyed by station code
+pub fn stations() -> &'static StationResponse {
+    STATIONS.get_or_init(|| {
+        serde_json::from_str(STATIONS_JSON).expect("the bundled station snapshot is valid")
+    })
+}
+
+/// Returns the bundled station with the provided code, ignoring case
+pub fn station(code: &str) -> Option<&'static Station> {
+    let stations = &stations().0;
+
+    stations.get(code).or_else(|| {
+        stations
+            .values()
+            .find(|station| station.code.eq_ignore_ascii_case(code))
+    })
+}
+
+/// Returns the bundled station with the provided name, ignoring case
+pub fn station_by_name(name: &str) -> Option<&'static Station> {
+    stations()
+        .0
+        .values()
+        .find(|station| station.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Returns the bundled stations whose name or city contains every word of
+/// the query as the start of one of their words, sorted by name
+///
+/// For example `"balt pen"` matches `Baltimore Penn`. Use [`fuzzy_search`]
+/// to also match typos, abbreviations and nicknames.
+pub fn search(query: &str) -> Vec<&'static Station> {
+    let query = words(query);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<_> = stations()
+        .0
+        .values()
+        .filter(|station| {
+            let candidates: Vec<_> = words(&station.name)
+                .into_iter()
+                .chain(words(&station.city))
+                .collect();
+
+            query.iter().all(|word| {
+                candidates
+                    .iter()
+                    .any(|candidate| candidate.starts_with(word))
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    matches
+}
+
+/// Searches the bundled stations with the default [`StationSearch`], the
+/// best match first
+///
+/// For example `"philly"` matches `Philadelphia 30th Street` and
+/// `"penn station"` ranks New York first.
+///
+/// [`StationSearch`]: crate::search::StationSearch
+pub fn fuzzy_search(query: &str) -> Vec<StationMatch<'static>> {
+    stations().search(query)
+}
@@ -1,10 +1,22 @@
 use std::fmt;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     RequestFailed(reqwest::Error),
     DeserializeFailed(serde_json::error::Error),
     ApiErrorResponse(String),
+    IoFailed(std::io::Error),
+    #[cfg(feature = "csv")]
+    CsvFailed(csv::Error),
+    #[cfg(feature = "gtfs")]
+    ZipFailed(zip::result::ZipError),
+    #[cfg(feature = "sqlite")]
+    SqliteFailed(rusqlite::Error),
+    #[cfg(feature = "notify")]
+    ConfigFailed(toml::de::Error),
+    #[cfg(feature = "notify")]
+    InvalidConfig(String),
 }
 
 impl std::error::Error for Error {}
@@ -21,6 +33,29 @@ impl fmt::Display for Error {
             Self::ApiErrorResponse(e) => {
                 write!(f, "API returned an error response: {}", e)
             }
+            Self::IoFailed(e) => {
+                write!(f, "Unable to read or write the file: {}", e)
+            }
+            #[cfg(feature = "csv")]
+            Self::CsvFailed(e) => {
+                write!(f, "Unable to read or write the CSV record: {}", e)
+            }
+            #[cfg(feature = "gtfs")]
+            Self::ZipFailed(e) => {
+                write!(f, "Unable to read the zip archive: {}", e)
+            }
+            #[cfg(feature = "sqlite")]
+            Self::SqliteFailed(e) => {
+                write!(f, "Unable to query the SQLite database: {}", e)
+            }
+            #[cfg(feature = "notify")]
+            Self::ConfigFailed(e) => {
+                write!(f, "Unable to parse the configuration: {}", e)
+            }
+            #[cfg(feature = "notify")]
+            Self::InvalidConfig(e) => {
+                write!(f, "Invalid configuration: {}", e)
+            }
         }
     }
 }
@@ -36,3 +71,37 @@ impl From<reqwest::Error> for Error {
         Self::RequestFailed(error)
     }
 }
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoFailed(error)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Self {
+        Self::CsvFailed(error)
+    }
+}
+
+#[cfg(feature = "gtfs")]
+impl From<zip::result::ZipError> for Error {
+    fn from(error: zip::result::ZipError) -> Self {
+        Self::ZipFailed(error)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::SqliteFailed(error)
+    }
+}
+
+#[cfg(feature = "notify")]
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Self::ConfigFailed(error)
+    }
+}
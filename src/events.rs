@@ -0,0 +1,226 @@
+//! Train events derived from consecutive responses
+//!
+//! The API only reports the current state of every train. This module compares
+//! two [`TrainResponse`] snapshots of the network and reports what happened in
+//! between as a list of [`TrainEvent`]s: trains arriving at or departing from
+//! a station and trains whose delay changed.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{events, Client};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Client::new();
+//!     let previous = client.trains().await?;
+//!     tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+//!     let current = client.trains().await?;
+//!
+//!     for event in events::diff(&previous, &current) {
+//!         println!("{:?}", event);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use crate::responses::{Train, TrainResponse, TrainStatus};
+
+/// What happened to a train between two snapshots
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrainEventKind {
+    /// The train arrived at the station
+    Arrival,
+
+    /// The train departed from the station
+    Departure,
+
+    /// The delay of the train changed, a negative value means the train is
+    /// running early.
+    DelayChange {
+        previous_delay_minutes: Option<i64>,
+        delay_minutes: Option<i64>,
+    },
+}
+
+/// A single event of a train
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TrainEvent {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// The station the event happened at. For a delay change this is the
+    /// next station of the train.
+    pub station_code: String,
+
+    /// When the event happened according to the API
+    pub timestamp: DateTime<FixedOffset>,
+
+    /// What happened
+    #[serde(flatten)]
+    pub kind: TrainEventKind,
+}
+
+impl TrainEvent {
+    fn new(
+        train: &Train,
+        station_code: &str,
+        timestamp: DateTime<FixedOffset>,
+        kind: TrainEventKind,
+    ) -> Self {
+        Self {
+            train_id: train.train_id.clone(),
+            train_num: train.train_num,
+            route_name: train.route_name.clone(),
+            station_code: station_code.to_string(),
+            timestamp,
+            kind,
+        }
+    }
+}
+
+/// Returns the events that happened to the trains between the `previous` and
+/// the `current` snapshots of the network.
+///
+/// Trains that only appear in one of the snapshots do not produce any events.
+/// The events are ordered by [`train_id`] and then in the order the train
+/// stops at its stations, with the delay change (if any) last.
+///
+/// [`train_id`]: Train::train_id
+pub fn diff(previous: &TrainResponse, current: &TrainResponse) -> Vec<TrainEvent> {
+    let previous: HashMap<_, _> = previous
+        .0
+        .values()
+        .flatten()
+        .map(|train| (train.train_id.as_str(), train))
+        .collect();
+
+    let mut trains: Vec<_> = current.iter_trains().collect();
+    trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+
+    trains
+        .into_iter()
+        .filter_map(|train| Some((previous.get(train.train_id.as_str())?, train)))
+        .flat_map(|(previous, current)| diff_train(previous, current))
+        .collect()
+}
+
+fn diff_train(previous: &Train, current: &Train) -> Vec<TrainEvent> {
+    let mut events = Vec::new();
+
+    for station in &current.stations {
+        let Some(previous_status) = previous
+            .stations
+            .iter()
+            .find(|previous| previous.code == station.code)
+            .map(|previous| previous.status)
+        else {
+            continue;
+        };
+
+        let arrived = matches!(previous_status, TrainStatus::Enroute)
+            && matches!(station.status, TrainStatus::Station | TrainStatus::Departed);
+        if arrived {
+            events.push(TrainEvent::new(
+                current,
+                &station.code,
+                station.arrival.unwrap_or(current.updated_at),
+                TrainEventKind::Arrival,
+            ));
+        }
+
+        let departed = matches!(previous_status, TrainStatus::Enroute | TrainStatus::Station)
+            && station.status == TrainStatus::Departed;
+        if departed {
+            events.push(TrainEvent::new(
+                current,
+                &station.code,
+                station.departure.unwrap_or(current.updated_at),
+                TrainEventKind::Departure,
+            ));
+        }
+    }
+
+    let previous_delay = previous.delay().map(|delay| delay.num_minutes());
+    let delay = current.delay().map(|delay| delay.num_minutes());
+    if previous_delay != delay {
+        let station_code = current
+            .remaining_stations()
+            .next()
+            .map_or(current.destination_code.as_str(), |station| {
+                station.code.as_str()
+            });
+
+        events.push(TrainEvent::new(
+            current,
+            station_code,
+            current.updated_at,
+            TrainEventKind::DelayChange {
+                previous_delay_minutes: previous_delay,
+                delay_minutes: delay,
+            },
+        ));
+    }
+
+    events
+}
+
+/// Selects the events a subscriber is interested in
+///
+/// Each list that is not empty must contain the matching property of the
+/// event, an empty list matches every event. For example a filter with one
+/// route and two stations matches the events of that route at either station.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct EventFilter {
+    /// The [`train_id`]s to match
+    ///
+    /// [`train_id`]: TrainEvent::train_id
+    pub train_ids: Vec<String>,
+
+    /// The [`route_name`]s to match, ignoring case
+    ///
+    /// [`route_name`]: TrainEvent::route_name
+    pub routes: Vec<String>,
+
+    /// The [`station_code`]s to match, ignoring case
+    ///
+    /// [`station_code`]: TrainEvent::station_code
+    pub stations: Vec<String>,
+}
+
+impl EventFilter {
+    /// Returns `true` if the event should be delivered to the subscriber
+    pub fn matches(&self, event: &TrainEvent) -> bool {
+        (self.train_ids.is_empty() || self.train_ids.contains(&event.train_id))
+            && (self.routes.is_empty()
+                || self
+                    .routes
+                    .iter()
+                    .any(|route| route.eq_ignore_ascii_case(&event.route_name)))
+            && (self.stations.is_empty()
+                || self
+                    .stations
+                    .iter()
+                    .any(|station| station.eq_ignore_ascii_case(&event.station_code)))
+    }
+}
@@ -0,0 +1,189 @@
+//! Tabular export of train stop events
+//!
+//! This module flattens a [`TrainResponse`] into one [`StopEvent`] row per
+//! train and station pair which is easier to load into spreadsheets, data
+//! frames or columnar formats than the nested API response.
+//!
+//! All timestamps are normalized to UTC since the API reports each station in
+//! its own local timezone. When the `csv` feature is enabled the rows can be
+//! written out using [`write_csv`].
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::responses::{Train, TrainResponse, TrainStatus};
+
+#[cfg(feature = "csv")]
+use crate::errors;
+
+/// A single stop of a train at a station
+///
+/// The field order of this struct is the column order used when exporting and
+/// will only ever be extended at the end.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct StopEvent {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// One based position of the station in the train's route
+    pub stop_sequence: u32,
+
+    /// The unique identification code of the station
+    pub station_code: String,
+
+    /// The scheduled arrival time at the station
+    pub scheduled_arrival: DateTime<Utc>,
+
+    /// The scheduled departure time from the station
+    pub scheduled_departure: DateTime<Utc>,
+
+    /// The actual arrival time at the station. This is a prediction when the
+    /// train is still [`Enroute`] to the station.
+    ///
+    /// [`Enroute`]: TrainStatus::Enroute
+    pub actual_arrival: Option<DateTime<Utc>>,
+
+    /// The actual departure time from the station. This is a prediction when
+    /// the train has not yet [`Departed`] the station.
+    ///
+    /// [`Departed`]: TrainStatus::Departed
+    pub actual_departure: Option<DateTime<Utc>>,
+
+    /// The difference in minutes between the actual and scheduled arrival, a
+    /// negative value means the train was early.
+    pub arrival_delay_minutes: Option<i64>,
+
+    /// The difference in minutes between the actual and scheduled departure,
+    /// a negative value means the train was early.
+    pub departure_delay_minutes: Option<i64>,
+
+    /// The status of the train for this station
+    pub status: TrainStatus,
+}
+
+impl StopEvent {
+    /// The names of the exported columns, in order
+    pub const COLUMNS: [&'static str; 12] = [
+        "train_id",
+        "train_num",
+        "route_name",
+        "stop_sequence",
+        "station_code",
+        "scheduled_arrival",
+        "scheduled_departure",
+        "actual_arrival",
+        "actual_departure",
+        "arrival_delay_minutes",
+        "departure_delay_minutes",
+        "status",
+    ];
+}
+
+impl Train {
+    /// Returns a [`StopEvent`] for each of the train's stations, in the order
+    /// the train stops at them.
+    pub fn stop_events(&self) -> impl Iterator<Item = StopEvent> + '_ {
+        self.stations
+            .iter()
+            .enumerate()
+            .map(move |(index, station)| StopEvent {
+                train_id: self.train_id.clone(),
+                train_num: self.train_num,
+                route_name: self.route_name.clone(),
+                stop_sequence: index as u32 + 1,
+                station_code: station.code.clone(),
+                scheduled_arrival: station.schedule_arrival.with_timezone(&Utc),
+                scheduled_departure: station.schedule_departure.with_timezone(&Utc),
+                actual_arrival: station.arrival.map(|arrival| arrival.with_timezone(&Utc)),
+                actual_departure: station
+                    .departure
+                    .map(|departure| departure.with_timezone(&Utc)),
+                arrival_delay_minutes: station
+                    .arrival
+                    .map(|arrival| (arrival - station.schedule_arrival).num_minutes()),
+                departure_delay_minutes: station
+                    .departure
+                    .map(|departure| (departure - station.schedule_departure).num_minutes()),
+                status: station.status,
+            })
+    }
+}
+
+impl TrainResponse {
+    /// Returns a [`StopEvent`] for every station of every train in the
+    /// response.
+    ///
+    /// The rows are sorted by [`train_id`] and then by stop sequence so that
+    /// exporting the same response always produces the same output.
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn stop_events(&self) -> Vec<StopEvent> {
+        let mut trains: Vec<_> = self.iter_trains().collect();
+        trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+
+        trains
+            .into_iter()
+            .flat_map(|train| train.stop_events())
+            .collect()
+    }
+
+    /// Writes every stop event of this response as CSV (including a header
+    /// row) to `writer`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     Client::new()
+    ///         .trains()
+    ///         .await?
+    ///         .write_csv(std::io::stdout())?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub fn write_csv<W: std::io::Write>(&self, writer: W) -> Result<(), errors::Error> {
+        write_csv(self.stop_events(), writer)
+    }
+}
+
+/// Writes the provided stop events as CSV (including a header row) to
+/// `writer`.
+///
+/// The columns are listed in [`StopEvent::COLUMNS`] and timestamps are
+/// written in RFC 3339 format in UTC. The header row is written even when
+/// there are no events.
+#[cfg(feature = "csv")]
+pub fn write_csv<I, W>(events: I, writer: W) -> Result<(), errors::Error>
+where
+    I: IntoIterator<Item = StopEvent>,
+    W: std::io::Write,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    writer.write_record(StopEvent::COLUMNS)?;
+
+    for event in events {
+        writer.serialize(event)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
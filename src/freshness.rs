@@ -0,0 +1,138 @@
+//! Stale data detection
+//!
+//! Amtraker sometimes keeps serving trains whose position has not changed for
+//! hours, for example after the train terminated without being marked as
+//! [`Completed`]. This module classifies how fresh the data of a train is
+//! relative to a supplied "now" and detects ghost trains: trains that are
+//! [`Active`] but whose data is stale.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{freshness::Freshness, Client};
+//! use chrono::{Duration, Utc};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mut response = Client::new().trains().await?;
+//!     let threshold = Duration::minutes(Freshness::DEFAULT_THRESHOLD_MINUTES);
+//!
+//!     for ghost in response.remove_ghost_trains(Utc::now(), threshold) {
+//!         println!("Dropped ghost train {}", ghost.train_id);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`Active`]: TrainState::Active
+//! [`Completed`]: TrainState::Completed
+
+use chrono::{DateTime, Duration, TimeZone};
+use serde::Serialize;
+
+use crate::responses::{Train, TrainResponse, TrainState};
+
+/// How fresh the data of a train is
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    /// The position of the train was reported within the threshold, or the
+    /// train was created too recently to have reported one
+    Fresh,
+
+    /// The API still refreshes the train but its position was not reported
+    /// within the threshold
+    PositionStale,
+
+    /// Neither the train nor its position were refreshed within the threshold
+    Stale,
+}
+
+impl Freshness {
+    /// A threshold suited to the refresh rate of the API, in minutes
+    pub const DEFAULT_THRESHOLD_MINUTES: i64 = 15;
+}
+
+impl Train {
+    /// Returns the time elapsed between the last position report of this
+    /// train (see [`last_value`]) and `now`
+    ///
+    /// [`last_value`]: Self::last_value
+    pub fn data_age<Tz: TimeZone>(&self, now: DateTime<Tz>) -> Duration {
+        now.fixed_offset() - self.last_value
+    }
+
+    /// Classifies the freshness of the data of this train
+    ///
+    /// The [`last_value`] (or the [`created_at`] of a train that was created
+    /// more recently) tells how old the position is, the [`updated_at`] tells
+    /// whether the API still refreshes the train at all. Data older than
+    /// `threshold` is stale.
+    ///
+    /// [`last_value`]: Self::last_value
+    /// [`created_at`]: Self::created_at
+    /// [`updated_at`]: Self::updated_at
+    pub fn freshness<Tz: TimeZone>(&self, now: DateTime<Tz>, threshold: Duration) -> Freshness {
+        let now = now.fixed_offset();
+
+        if now - self.last_value.max(self.created_at) <= threshold {
+            Freshness::Fresh
+        } else if now - self.updated_at <= threshold {
+            Freshness::PositionStale
+        } else {
+            Freshness::Stale
+        }
+    }
+
+    /// Returns `true` if this train is [`Active`] but its data is not
+    /// [`Fresh`]
+    ///
+    /// [`Active`]: TrainState::Active
+    /// [`Fresh`]: Freshness::Fresh
+    pub fn is_ghost<Tz: TimeZone>(&self, now: DateTime<Tz>, threshold: Duration) -> bool {
+        self.train_state == TrainState::Active && self.freshness(now, threshold) != Freshness::Fresh
+    }
+}
+
+impl TrainResponse {
+    /// Returns the ghost trains of this response, see [`Train::is_ghost`]
+    pub fn ghost_trains<Tz: TimeZone>(
+        &self,
+        now: DateTime<Tz>,
+        threshold: Duration,
+    ) -> impl Iterator<Item = &Train> + '_ {
+        let now = now.fixed_offset();
+
+        self.iter_trains()
+            .filter(move |train| train.is_ghost(now, threshold))
+    }
+
+    /// Removes the ghost trains from this response and returns them sorted by
+    /// [`train_id`], see [`Train::is_ghost`]
+    ///
+    /// Train numbers left without any train are removed as well.
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn remove_ghost_trains<Tz: TimeZone>(
+        &mut self,
+        now: DateTime<Tz>,
+        threshold: Duration,
+    ) -> Vec<Train> {
+        let now = now.fixed_offset();
+        let mut removed = Vec::new();
+
+        self.0.retain(|_, trains| {
+            let (ghosts, kept): (Vec<_>, Vec<_>) = std::mem::take(trains)
+                .into_iter()
+                .partition(|train| train.is_ghost(now, threshold));
+            *trains = kept;
+            removed.extend(ghosts);
+
+            !trains.is_empty()
+        });
+
+        removed.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+        removed
+    }
+}
@@ -0,0 +1,278 @@
+//! Geographic helpers
+//!
+//! This module allows the caller to ask spatial questions about the trains
+//! and stations returned by the API, for example "which trains are currently
+//! inside the Northeast Corridor" or "which trains will still pass through
+//! California". Regions are described using the [`Region`] trait which is
+//! implemented by [`BoundingBox`] and [`Polygon`].
+//!
+//! All distances are expressed in miles to match the units of the
+//! [`velocity`] field.
+//!
+//! [`velocity`]: crate::responses::Train::velocity
+
+use crate::responses::{Heading, Station, StationResponse, Train, TrainResponse};
+
+/// Mean radius of the earth in miles
+const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+/// A point on the surface of the earth
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Coordinate {
+    /// The latitude of the point in degrees
+    pub lat: f64,
+
+    /// The longitude of the point in degrees
+    pub lon: f64,
+}
+
+impl Coordinate {
+    /// Creates a new coordinate from a latitude and longitude in degrees
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Returns the great-circle distance in miles between two coordinates
+    /// using the haversine formula.
+    pub fn distance_to(&self, other: Coordinate) -> f64 {
+        let lat1 = self.lat.to_radians();
+        let lat2 = other.lat.to_radians();
+        let delta_lat = (other.lat - self.lat).to_radians();
+        let delta_lon = (other.lon - self.lon).to_radians();
+
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_MILES * a.sqrt().asin()
+    }
+
+    /// Returns the initial compass bearing in degrees of the great-circle
+    /// path from this coordinate to another one, `0.0` being north and
+    /// `90.0` being east.
+    pub fn bearing_to(&self, other: Coordinate) -> f64 {
+        let lat1 = self.lat.to_radians();
+        let lat2 = other.lat.to_radians();
+        let delta_lon = (other.lon - self.lon).to_radians();
+
+        let y = delta_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos();
+
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Returns the coordinate reached by travelling `distance` miles from
+    /// this coordinate along the provided compass bearing in degrees.
+    pub fn destination(&self, bearing: f64, distance: f64) -> Coordinate {
+        let lat1 = self.lat.to_radians();
+        let lon1 = self.lon.to_radians();
+        let bearing = bearing.to_radians();
+        let angle = distance / EARTH_RADIUS_MILES;
+
+        let lat2 = (lat1.sin() * angle.cos() + lat1.cos() * angle.sin() * bearing.cos()).asin();
+        let lon2 = lon1
+            + (bearing.sin() * angle.sin() * lat1.cos())
+                .atan2(angle.cos() - lat1.sin() * lat2.sin());
+
+        Coordinate::new(lat2.to_degrees(), lon2.to_degrees())
+    }
+}
+
+/// An area on the surface of the earth
+pub trait Region {
+    /// Returns `true` if the provided coordinate lies inside the region
+    fn contains(&self, coordinate: Coordinate) -> bool;
+}
+
+/// A rectangular region aligned with the lines of latitude and longitude
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    /// The south west (bottom left) corner of the box
+    pub south_west: Coordinate,
+
+    /// The north east (top right) corner of the box
+    pub north_east: Coordinate,
+}
+
+impl BoundingBox {
+    /// Creates a new bounding box from its south west and north east corners
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use amtrak_api::geo::{BoundingBox, Coordinate, Region};
+    ///
+    /// let pennsylvania = BoundingBox::new(
+    ///     Coordinate::new(39.72, -80.52),
+    ///     Coordinate::new(42.27, -74.69),
+    /// );
+    ///
+    /// assert!(pennsylvania.contains(Coordinate::new(39.9557, -75.1820)));
+    /// ```
+    pub fn new(south_west: Coordinate, north_east: Coordinate) -> Self {
+        Self {
+            south_west,
+            north_east,
+        }
+    }
+}
+
+impl Region for BoundingBox {
+    fn contains(&self, coordinate: Coordinate) -> bool {
+        (self.south_west.lat..=self.north_east.lat).contains(&coordinate.lat)
+            && (self.south_west.lon..=self.north_east.lon).contains(&coordinate.lon)
+    }
+}
+
+/// An arbitrary region described by a closed ring of vertices
+///
+/// The ring is implicitly closed, there is no need to repeat the first vertex
+/// at the end of the list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Coordinate>,
+}
+
+impl Polygon {
+    /// Creates a new polygon from the provided vertices
+    pub fn new(vertices: Vec<Coordinate>) -> Self {
+        Self { vertices }
+    }
+
+    /// Returns the vertices that make up this polygon
+    pub fn vertices(&self) -> &[Coordinate] {
+        &self.vertices
+    }
+}
+
+impl Region for Polygon {
+    /// Uses the even-odd ray casting rule, treating latitude and longitude as
+    /// planar coordinates.
+    fn contains(&self, coordinate: Coordinate) -> bool {
+        let mut inside = false;
+        let mut previous = match self.vertices.last() {
+            Some(vertex) => vertex,
+            None => return false,
+        };
+
+        for current in &self.vertices {
+            if (current.lat > coordinate.lat) != (previous.lat > coordinate.lat) {
+                let crossing_lon = current.lon
+                    + (coordinate.lat - current.lat) / (previous.lat - current.lat)
+                        * (previous.lon - current.lon);
+
+                if coordinate.lon < crossing_lon {
+                    inside = !inside;
+                }
+            }
+
+            previous = current;
+        }
+
+        inside
+    }
+}
+
+impl<R: Region + ?Sized> Region for &R {
+    fn contains(&self, coordinate: Coordinate) -> bool {
+        (**self).contains(coordinate)
+    }
+}
+
+impl Heading {
+    /// Returns the compass bearing of this heading in degrees, `0.0` being
+    /// north and `90.0` being east.
+    pub fn bearing(&self) -> f64 {
+        match self {
+            Self::N => 0.0,
+            Self::NE => 45.0,
+            Self::E => 90.0,
+            Self::SE => 135.0,
+            Self::S => 180.0,
+            Self::SW => 225.0,
+            Self::W => 270.0,
+            Self::NW => 315.0,
+        }
+    }
+}
+
+impl Train {
+    /// Returns the last reported position of this train
+    pub fn coordinate(&self) -> Coordinate {
+        Coordinate::new(self.lat, self.lon)
+    }
+
+    /// Returns `true` if the remaining route of this train passes through the
+    /// provided region.
+    ///
+    /// The remaining route is made up of the current position of the train
+    /// and every station that the train has not yet departed from. Station
+    /// coordinates are looked up in `stations`, stations missing from it are
+    /// ignored.
+    pub fn passes_through<R: Region + ?Sized>(
+        &self,
+        region: &R,
+        stations: &StationResponse,
+    ) -> bool {
+        region.contains(self.coordinate())
+            || self
+                .remaining_stations()
+                .filter_map(|train_station| stations.0.get(&train_station.code))
+                .any(|station| region.contains(station.coordinate()))
+    }
+}
+
+impl Station {
+    /// Returns the position of this station
+    pub fn coordinate(&self) -> Coordinate {
+        Coordinate::new(self.lat, self.lon)
+    }
+}
+
+impl TrainResponse {
+    /// Returns all trains whose last reported position lies inside `region`
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::{
+    ///     geo::{BoundingBox, Coordinate},
+    ///     Client,
+    /// };
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let california = BoundingBox::new(
+    ///         Coordinate::new(32.53, -124.48),
+    ///         Coordinate::new(42.01, -114.13),
+    ///     );
+    ///
+    ///     Client::new()
+    ///         .trains()
+    ///         .await?
+    ///         .trains_within(&california)
+    ///         .for_each(|train| println!("{} is in California", train.train_id));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn trains_within<'a, R: Region + ?Sized>(
+        &'a self,
+        region: &'a R,
+    ) -> impl Iterator<Item = &'a Train> + 'a {
+        self.iter_trains()
+            .filter(move |train| region.contains(train.coordinate()))
+    }
+
+    /// Returns all trains whose remaining route passes through `region`
+    ///
+    /// See [`Train::passes_through`] for a description of what is considered
+    /// the remaining route of a train.
+    pub fn trains_passing_through<'a, R: Region + ?Sized>(
+        &'a self,
+        region: &'a R,
+        stations: &'a StationResponse,
+    ) -> impl Iterator<Item = &'a Train> + 'a {
+        self.iter_trains()
+            .filter(move |train| train.passes_through(region, stations))
+    }
+}
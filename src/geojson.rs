@@ -0,0 +1,181 @@
+//! GeoJSON export
+//!
+//! This module converts the responses returned by the API into [GeoJSON]
+//! `FeatureCollection`s that can be handed directly to mapping libraries such
+//! as Leaflet or MapLibre.
+//!
+//! * Every [`Train`] is exported as a `Point` feature positioned at its last
+//!   reported location.
+//! * Every [`Station`] is exported as a `Point` feature.
+//! * The route of a [`Train`] can be exported as a `LineString` feature which
+//!   connects each of its [`stations`] in order. Since [`TrainStation`] does
+//!   not carry a position, the coordinates are looked up in a
+//!   [`StationResponse`].
+//!
+//! [GeoJSON]: https://datatracker.ietf.org/doc/html/rfc7946
+//! [`stations`]: Train::stations
+//! [`TrainStation`]: crate::responses::TrainStation
+
+use serde_json::{json, Value};
+
+use crate::{
+    geo::Coordinate,
+    responses::{Station, StationResponse, Train, TrainResponse},
+};
+
+/// Returns a GeoJSON position (`[longitude, latitude]`) for the coordinate
+fn position(coordinate: Coordinate) -> Value {
+    json!([coordinate.lon, coordinate.lat])
+}
+
+/// Wraps a list of features into a `FeatureCollection`
+fn feature_collection(features: Vec<Value>) -> Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+impl Train {
+    /// Returns this train as a GeoJSON `Point` feature
+    ///
+    /// The feature's `id` is the [`train_id`] and the properties contain the
+    /// route name, origin, destination, heading, state, velocity and the
+    /// delay in minutes (`null` when unknown, negative when early).
+    ///
+    /// [`train_id`]: Self::train_id
+    pub fn to_geojson(&self) -> Value {
+        json!({
+            "type": "Feature",
+            "id": self.train_id,
+            "geometry": {
+                "type": "Point",
+                "coordinates": position(self.coordinate()),
+            },
+            "properties": {
+                "trainId": self.train_id,
+                "trainNum": self.train_num,
+                "routeName": self.route_name,
+                "originCode": self.origin_code,
+                "destinationCode": self.destination_code,
+                "delayMinutes": self.delay().map(|delay| delay.num_minutes()),
+                "heading": self.heading,
+                "state": self.train_state,
+                "velocity": self.velocity,
+                "updatedAt": self.updated_at.to_rfc3339(),
+            },
+        })
+    }
+
+    /// Returns the route of this train as a GeoJSON `LineString` feature
+    ///
+    /// The line connects each of the train's stations in the order the train
+    /// stops at them. Stations missing from `stations` are skipped. Returns
+    /// `None` if less than two stations could be located since a
+    /// `LineString` requires at least two positions.
+    pub fn route_to_geojson(&self, stations: &StationResponse) -> Option<Value> {
+        let coordinates: Vec<_> = self
+            .stations
+            .iter()
+            .filter_map(|train_station| stations.0.get(&train_station.code))
+            .map(|station| position(station.coordinate()))
+            .collect();
+
+        if coordinates.len() < 2 {
+            return None;
+        }
+
+        let stops: Vec<_> = self
+            .stations
+            .iter()
+            .map(|train_station| train_station.code.as_str())
+            .collect();
+
+        Some(json!({
+            "type": "Feature",
+            "id": self.train_id,
+            "geometry": {
+                "type": "LineString",
+                "coordinates": coordinates,
+            },
+            "properties": {
+                "trainId": self.train_id,
+                "routeName": self.route_name,
+                "stops": stops,
+            },
+        }))
+    }
+}
+
+impl Station {
+    /// Returns this station as a GeoJSON `Point` feature
+    ///
+    /// The feature's `id` is the station [`code`].
+    ///
+    /// [`code`]: Self::code
+    pub fn to_geojson(&self) -> Value {
+        json!({
+            "type": "Feature",
+            "id": self.code,
+            "geometry": {
+                "type": "Point",
+                "coordinates": position(self.coordinate()),
+            },
+            "properties": {
+                "code": self.code,
+                "name": self.name,
+                "city": self.city,
+                "state": self.state,
+                "tz": self.tz,
+                "trains": self.trains,
+            },
+        })
+    }
+}
+
+impl TrainResponse {
+    /// Returns a GeoJSON `FeatureCollection` containing a `Point` feature for
+    /// every train in this response.
+    ///
+    /// See [`Train::to_geojson`] for the properties of each feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let geojson = Client::new().trains().await?.to_geojson();
+    ///     std::fs::write("trains.geojson", geojson.to_string())?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_geojson(&self) -> Value {
+        feature_collection(self.iter_trains().map(Train::to_geojson).collect())
+    }
+
+    /// Returns a GeoJSON `FeatureCollection` containing a `LineString`
+    /// feature for the route of every train in this response.
+    ///
+    /// See [`Train::route_to_geojson`] for how each line is built. Trains
+    /// whose route could not be located are left out.
+    pub fn routes_to_geojson(&self, stations: &StationResponse) -> Value {
+        feature_collection(
+            self.iter_trains()
+                .filter_map(|train| train.route_to_geojson(stations))
+                .collect(),
+        )
+    }
+}
+
+impl StationResponse {
+    /// Returns a GeoJSON `FeatureCollection` containing a `Point` feature for
+    /// every station in this response.
+    ///
+    /// See [`Station::to_geojson`] for the properties of each feature.
+    pub fn to_geojson(&self) -> Value {
+        feature_collection(self.0.values().map(Station::to_geojson).collect())
+    }
+}
@@ -0,0 +1,593 @@
+//! Static GTFS feed support
+//!
+//! Amtrak publishes its schedule as a static [GTFS] feed. This module loads
+//! such a feed (either an extracted directory or the zip archive) and joins it
+//! with the live data returned by the API. It is only available when the
+//! `gtfs` feature is enabled.
+//!
+//! Live trains are matched to GTFS trips using the [`train_num`] which Amtrak
+//! publishes as the `trip_short_name` of a trip. When several trips share the
+//! same number (for example a weekday and a weekend variant), the trip whose
+//! service runs on the train's departure date and whose stops best match the
+//! train's [`stations`] is picked. Stations are matched to GTFS stops using
+//! the station [`code`] which Amtrak uses as its `stop_id`.
+//!
+//! [GTFS]: https://gtfs.org/schedule/reference/
+//! [`train_num`]: Train::train_num
+//! [`stations`]: Train::stations
+//! [`code`]: crate::responses::TrainStation::code
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::{
+    errors,
+    geo::Coordinate,
+    responses::{Train, TrainResponse},
+    shape::{RouteShape, RouteShapes},
+};
+
+pub type Result<T> = std::result::Result<T, errors::Error>;
+
+/// A row of `routes.txt`
+#[derive(Debug, Deserialize, Clone)]
+pub struct Route {
+    /// Identifies the route
+    pub route_id: String,
+
+    /// Short name of the route
+    #[serde(default)]
+    pub route_short_name: Option<String>,
+
+    /// Full name of the route
+    ///
+    /// # Examples:
+    /// * `Keystone`
+    /// * `Northeast Regional`
+    #[serde(default)]
+    pub route_long_name: Option<String>,
+
+    /// Route color designation as a six digit hexadecimal number
+    #[serde(default)]
+    pub route_color: Option<String>,
+
+    /// Legible color to use for text drawn against a background of
+    /// [`route_color`]
+    ///
+    /// [`route_color`]: Self::route_color
+    #[serde(default)]
+    pub route_text_color: Option<String>,
+}
+
+/// A row of `trips.txt`
+#[derive(Debug, Deserialize, Clone)]
+pub struct Trip {
+    /// Identifies the route this trip belongs to
+    pub route_id: String,
+
+    /// Identifies the set of dates when service is available for this trip
+    pub service_id: String,
+
+    /// Identifies the trip
+    pub trip_id: String,
+
+    /// Public facing text used to identify the trip, Amtrak uses the train
+    /// number.
+    #[serde(default)]
+    pub trip_short_name: Option<String>,
+
+    /// Text that appears on signage identifying the trip's destination
+    #[serde(default)]
+    pub trip_headsign: Option<String>,
+
+    /// Indicates the direction of travel for this trip
+    #[serde(default)]
+    pub direction_id: Option<u8>,
+
+    /// Identifies the geospatial shape describing the path of this trip
+    #[serde(default)]
+    pub shape_id: Option<String>,
+}
+
+/// A row of `stops.txt`
+#[derive(Debug, Deserialize, Clone)]
+pub struct Stop {
+    /// Identifies the stop, Amtrak uses the station code
+    pub stop_id: String,
+
+    /// Name of the stop
+    #[serde(default)]
+    pub stop_name: Option<String>,
+
+    /// Latitude of the stop
+    pub stop_lat: f64,
+
+    /// Longitude of the stop
+    pub stop_lon: f64,
+
+    /// Timezone of the stop
+    #[serde(default)]
+    pub stop_timezone: Option<String>,
+}
+
+/// A row of `stop_times.txt`
+#[derive(Debug, Deserialize, Clone)]
+pub struct StopTime {
+    /// Identifies the trip this stop time belongs to
+    pub trip_id: String,
+
+    /// Scheduled arrival time (`HH:MM:SS`, may exceed `24:00:00` for trips
+    /// running past midnight)
+    #[serde(default)]
+    pub arrival_time: Option<String>,
+
+    /// Scheduled departure time (`HH:MM:SS`, may exceed `24:00:00` for trips
+    /// running past midnight)
+    #[serde(default)]
+    pub departure_time: Option<String>,
+
+    /// Identifies the serviced stop
+    pub stop_id: String,
+
+    /// Order of the stop for this trip
+    pub stop_sequence: u32,
+}
+
+/// A row of `shapes.txt`
+#[derive(Debug, Deserialize, Clone)]
+pub struct ShapePoint {
+    /// Identifies the shape this point belongs to
+    pub shape_id: String,
+
+    /// Latitude of the point
+    pub shape_pt_lat: f64,
+
+    /// Longitude of the point
+    pub shape_pt_lon: f64,
+
+    /// Order of the point along the shape
+    pub shape_pt_sequence: u32,
+
+    /// Distance traveled along the shape from the first point
+    #[serde(default)]
+    pub shape_dist_traveled: Option<f64>,
+}
+
+/// A row of `calendar.txt`
+#[derive(Debug, Deserialize, Clone)]
+struct Calendar {
+    service_id: String,
+    monday: u8,
+    tuesday: u8,
+    wednesday: u8,
+    thursday: u8,
+    friday: u8,
+    saturday: u8,
+    sunday: u8,
+    #[serde(deserialize_with = "deserialize_date")]
+    start_date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_date")]
+    end_date: NaiveDate,
+}
+
+impl Calendar {
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        let runs_on_weekday = match date.weekday() {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        };
+
+        runs_on_weekday == 1 && (self.start_date..=self.end_date).contains(&date)
+    }
+}
+
+/// A row of `calendar_dates.txt`
+#[derive(Debug, Deserialize, Clone)]
+struct CalendarDate {
+    service_id: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    date: NaiveDate,
+    exception_type: u8,
+}
+
+/// Deserializes a GTFS date in the `YYYYMMDD` format
+fn deserialize_date<'de, D>(deserializer: D) -> std::result::Result<NaiveDate, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date = String::deserialize(deserializer)?;
+    NaiveDate::parse_from_str(&date, "%Y%m%d").map_err(serde::de::Error::custom)
+}
+
+/// Where the files of a feed are read from
+enum Source {
+    Directory(PathBuf),
+    Zip(zip::ZipArchive<File>),
+}
+
+impl Source {
+    /// Reads every row of the file `name`. Returns an empty list if the file
+    /// is optional and missing from the feed.
+    fn table<T: DeserializeOwned>(&mut self, name: &str, required: bool) -> Result<Vec<T>> {
+        match self {
+            Self::Directory(directory) => match File::open(directory.join(name)) {
+                Ok(file) => read_table(file),
+                Err(e) if e.kind() == io::ErrorKind::NotFound && !required => Ok(Vec::new()),
+                Err(e) => Err(e.into()),
+            },
+            Self::Zip(archive) => match archive.by_name(name) {
+                Ok(file) => read_table(file),
+                Err(zip::result::ZipError::FileNotFound) if !required => Ok(Vec::new()),
+                Err(e) => Err(e.into()),
+            },
+        }
+    }
+}
+
+fn read_table<T: DeserializeOwned, R: Read>(reader: R) -> Result<Vec<T>> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .into_deserialize()
+        .map(|row| row.map_err(errors::Error::from))
+        .collect()
+}
+
+/// A static GTFS feed loaded in memory
+#[derive(Debug, Clone)]
+pub struct GtfsFeed {
+    routes: HashMap<String, Route>,
+    trips: HashMap<String, Trip>,
+    stops: HashMap<String, Stop>,
+    stop_times: HashMap<String, Vec<StopTime>>,
+    shapes: HashMap<String, Vec<ShapePoint>>,
+    calendars: HashMap<String, Calendar>,
+    calendar_dates: HashMap<(String, NaiveDate), u8>,
+    trips_by_number: HashMap<String, Vec<String>>,
+}
+
+impl GtfsFeed {
+    /// Loads a feed from either an extracted directory or a zip archive
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::{gtfs::GtfsFeed, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let feed = GtfsFeed::open("GTFS.zip")?;
+    ///     let response = Client::new().trains().await?;
+    ///
+    ///     for (train, matched) in feed.match_response(&response) {
+    ///         println!(
+    ///             "{} runs trip {} (color {:?})",
+    ///             train.train_id,
+    ///             matched.trip.trip_id,
+    ///             matched.route_color()
+    ///         );
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+
+        if path.is_dir() {
+            Self::from_dir(path)
+        } else {
+            Self::from_zip(path)
+        }
+    }
+
+    /// Loads a feed from a directory containing the extracted GTFS files
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load(Source::Directory(path.as_ref().to_path_buf()))
+    }
+
+    /// Loads a feed from a GTFS zip archive
+    pub fn from_zip<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load(Source::Zip(zip::ZipArchive::new(File::open(path)?)?))
+    }
+
+    fn load(mut source: Source) -> Result<Self> {
+        let routes = source.table::<Route>("routes.txt", true)?;
+        let trips = source.table::<Trip>("trips.txt", true)?;
+        let stops = source.table::<Stop>("stops.txt", true)?;
+        let stop_times = source.table::<StopTime>("stop_times.txt", true)?;
+        let shapes = source.table::<ShapePoint>("shapes.txt", false)?;
+        let calendars = source.table::<Calendar>("calendar.txt", false)?;
+        let calendar_dates = source.table::<CalendarDate>("calendar_dates.txt", false)?;
+
+        let mut trips_by_number: HashMap<String, Vec<String>> = HashMap::new();
+        for trip in &trips {
+            if let Some(number) = &trip.trip_short_name {
+                trips_by_number
+                    .entry(number.clone())
+                    .or_default()
+                    .push(trip.trip_id.clone());
+            }
+        }
+
+        let mut grouped_stop_times: HashMap<String, Vec<StopTime>> = HashMap::new();
+        for stop_time in stop_times {
+            grouped_stop_times
+                .entry(stop_time.trip_id.clone())
+                .or_default()
+                .push(stop_time);
+        }
+        grouped_stop_times
+            .values_mut()
+            .for_each(|stop_times| stop_times.sort_by_key(|stop_time| stop_time.stop_sequence));
+
+        let mut grouped_shapes: HashMap<String, Vec<ShapePoint>> = HashMap::new();
+        for point in shapes {
+            grouped_shapes
+                .entry(point.shape_id.clone())
+                .or_default()
+                .push(point);
+        }
+        grouped_shapes
+            .values_mut()
+            .for_each(|points| points.sort_by_key(|point| point.shape_pt_sequence));
+
+        Ok(Self {
+            routes: routes
+                .into_iter()
+                .map(|route| (route.route_id.clone(), route))
+                .collect(),
+            trips: trips
+                .into_iter()
+                .map(|trip| (trip.trip_id.clone(), trip))
+                .collect(),
+            stops: stops
+                .into_iter()
+                .map(|stop| (stop.stop_id.clone(), stop))
+                .collect(),
+            stop_times: grouped_stop_times,
+            shapes: grouped_shapes,
+            calendars: calendars
+                .into_iter()
+                .map(|calendar| (calendar.service_id.clone(), calendar))
+                .collect(),
+            calendar_dates: calendar_dates
+                .into_iter()
+                .map(|date| ((date.service_id, date.date), date.exception_type))
+                .collect(),
+            trips_by_number,
+        })
+    }
+
+    /// Returns the route with the provided `route_id`
+    pub fn route(&self, route_id: &str) -> Option<&Route> {
+        self.routes.get(route_id)
+    }
+
+    /// Returns the trip with the provided `trip_id`
+    pub fn trip(&self, trip_id: &str) -> Option<&Trip> {
+        self.trips.get(trip_id)
+    }
+
+    /// Returns the stop with the provided `stop_id`
+    pub fn stop(&self, stop_id: &str) -> Option<&Stop> {
+        self.stops.get(stop_id)
+    }
+
+    /// Returns the stop times of the trip with the provided `trip_id` ordered
+    /// by `stop_sequence`
+    pub fn stop_times(&self, trip_id: &str) -> &[StopTime] {
+        self.stop_times
+            .get(trip_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the points of the shape with the provided `shape_id` ordered by
+    /// `shape_pt_sequence`
+    pub fn shape(&self, shape_id: &str) -> &[ShapePoint] {
+        self.shapes
+            .get(shape_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the shapes of every route keyed by its `route_long_name`, which
+    /// is the [`route_name`] of the live trains.
+    ///
+    /// When the trips of a route use several shapes, the one with the most
+    /// points is kept.
+    ///
+    /// [`route_name`]: Train::route_name
+    pub fn route_shapes(&self) -> RouteShapes {
+        let mut chosen: HashMap<&str, &str> = HashMap::new();
+        for trip in self.trips.values() {
+            let (Some(shape_id), Some(route_name)) = (
+                trip.shape_id.as_deref(),
+                self.route(&trip.route_id)
+                    .and_then(|route| route.route_long_name.as_deref()),
+            ) else {
+                continue;
+            };
+
+            let better = |current: &&str| {
+                (self.shape(shape_id).len(), Reverse(shape_id))
+                    > (self.shape(current).len(), Reverse(*current))
+            };
+            if chosen.get(route_name).is_none_or(better) {
+                chosen.insert(route_name, shape_id);
+            }
+        }
+
+        let mut shapes = RouteShapes::new();
+        for (route_name, shape_id) in chosen {
+            let points = self
+                .shape(shape_id)
+                .iter()
+                .map(|point| Coordinate::new(point.shape_pt_lat, point.shape_pt_lon))
+                .collect();
+            shapes.insert(route_name, RouteShape::new(points));
+        }
+
+        shapes
+    }
+
+    /// Returns whether the service runs on the provided date or `None` if the
+    /// feed does not contain any calendar information for the service.
+    fn service_runs_on(&self, service_id: &str, date: NaiveDate) -> Option<bool> {
+        match self.calendar_dates.get(&(service_id.to_string(), date)) {
+            Some(1) => Some(true),
+            Some(2) => Some(false),
+            _ => self
+                .calendars
+                .get(service_id)
+                .map(|calendar| calendar.runs_on(date)),
+        }
+    }
+
+    /// Returns the GTFS trip that the provided live train is running
+    ///
+    /// See the [module documentation](self) for how the trip is chosen.
+    pub fn trip_for_train(&self, train: &Train) -> Option<&Trip> {
+        let service_date = train
+            .stations
+            .first()
+            .map(|station| station.schedule_departure.date_naive());
+
+        self.trips_by_number
+            .get(&train.train_num.to_string())?
+            .iter()
+            .filter_map(|trip_id| self.trips.get(trip_id))
+            .max_by_key(|trip| {
+                let runs =
+                    service_date.and_then(|date| self.service_runs_on(&trip.service_id, date));
+                let runs_score = match runs {
+                    Some(true) => 2,
+                    None => 1,
+                    Some(false) => 0,
+                };
+
+                let stop_times = self.stop_times(&trip.trip_id);
+                let shared_stops = train
+                    .stations
+                    .iter()
+                    .filter(|station| {
+                        stop_times
+                            .iter()
+                            .any(|stop_time| stop_time.stop_id == station.code)
+                    })
+                    .count();
+
+                (runs_score, shared_stops, Reverse(trip.trip_id.as_str()))
+            })
+    }
+
+    /// Matches the provided live train with its GTFS trip, route, shape and
+    /// stop times. Returns `None` if no trip could be found.
+    pub fn match_train(&self, train: &Train) -> Option<TrainMatch<'_>> {
+        let trip = self.trip_for_train(train)?;
+        let stop_times = self.stop_times(&trip.trip_id);
+
+        Some(TrainMatch {
+            trip,
+            route: self.routes.get(&trip.route_id),
+            shape: trip
+                .shape_id
+                .as_deref()
+                .map(|shape_id| self.shape(shape_id))
+                .unwrap_or_default(),
+            stop_times: train
+                .stations
+                .iter()
+                .map(|station| {
+                    stop_times
+                        .iter()
+                        .find(|stop_time| stop_time.stop_id == station.code)
+                })
+                .collect(),
+        })
+    }
+
+    /// Matches every train of the response, trains without a matching trip
+    /// are skipped.
+    pub fn match_response<'a>(
+        &'a self,
+        response: &'a TrainResponse,
+    ) -> impl Iterator<Item = (&'a Train, TrainMatch<'a>)> + 'a {
+        response
+            .iter_trains()
+            .filter_map(|train| self.match_train(train).map(|matched| (train, matched)))
+    }
+}
+
+/// The GTFS data associated with a live [`Train`]
+#[derive(Debug, Clone)]
+pub struct TrainMatch<'a> {
+    /// The trip the train is running
+    pub trip: &'a Trip,
+
+    /// The route of the trip
+    pub route: Option<&'a Route>,
+
+    /// The shape of the trip, empty if the feed has no shape for it
+    pub shape: &'a [ShapePoint],
+
+    /// The stop time of each of the train's [`stations`], in the same order.
+    /// An entry is `None` when the station is not part of the GTFS trip.
+    ///
+    /// [`stations`]: Train::stations
+    pub stop_times: Vec<Option<&'a StopTime>>,
+}
+
+impl TrainMatch<'_> {
+    /// Returns the color of the route, if the feed provides one
+    pub fn route_color(&self) -> Option<&str> {
+        self.route.and_then(|route| route.route_color.as_deref())
+    }
+
+    /// Returns the GTFS `stop_sequence` of the station with the provided code
+    pub fn stop_sequence(&self, station_code: &str) -> Option<u32> {
+        self.stop_times
+            .iter()
+            .flatten()
+            .find(|stop_time| stop_time.stop_id == station_code)
+            .map(|stop_time| stop_time.stop_sequence)
+    }
+}
+
+#[cfg(feature = "gtfs-rt")]
+impl GtfsFeed {
+    /// Returns [`FeedOptions`] that use the trip ids, route ids and stop
+    /// sequences of this feed when generating a GTFS-Realtime feed. Every
+    /// train is matched once per feed, trains without a matching trip keep
+    /// the identifiers provided by the API.
+    ///
+    /// [`FeedOptions`]: crate::gtfs_rt::FeedOptions
+    pub fn feed_options(self: std::sync::Arc<Self>) -> crate::gtfs_rt::FeedOptions {
+        crate::gtfs_rt::FeedOptions::new().with_static_trip(move |train| {
+            self.match_train(train)
+                .map(|matched| crate::gtfs_rt::StaticTrip {
+                    trip_id: matched.trip.trip_id.clone(),
+                    route_id: matched.trip.route_id.clone(),
+                    stop_sequences: matched
+                        .stop_times
+                        .iter()
+                        .map(|stop_time| stop_time.map(|stop_time| stop_time.stop_sequence))
+                        .collect(),
+                })
+        })
+    }
+}
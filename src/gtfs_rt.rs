@@ -0,0 +1,604 @@
+//! GTFS-Realtime feed generation
+//!
+//! This module converts a [`TrainResponse`] into a [GTFS-Realtime]
+//! `FeedMessage` so that the data can be consumed by standard transit tooling.
+//! It is only available when the `gtfs-rt` feature is enabled.
+//!
+//! Every train in the response produces two entities:
+//!
+//! * A `TripUpdate` (with the id `trip-{train_id}`) containing a
+//!   `StopTimeUpdate` for each of the train's [`stations`] based on the
+//!   arrival and departure predictions.
+//! * A `VehiclePosition` (with the id `vehicle-{train_id}`) built from the
+//!   train's position, heading and velocity.
+//!
+//! By default the trip id is the [`train_id`], the route id is the
+//! [`route_name`] and the stop id is the station [`code`]. These identifiers
+//! rarely match the ones used by a static GTFS feed, so [`FeedOptions`]
+//! allows the caller to provide their own mapping.
+//!
+//! [GTFS-Realtime]: https://gtfs.org/realtime/reference/
+//! [`stations`]: Train::stations
+//! [`train_id`]: Train::train_id
+//! [`route_name`]: Train::route_name
+//! [`code`]: TrainStation::code
+
+use chrono::{DateTime, TimeZone};
+
+use crate::responses::{Train, TrainResponse, TrainStation, TrainStatus};
+
+pub use transit_realtime::*;
+
+/// The version of the GTFS-Realtime specification this module produces
+const GTFS_REALTIME_VERSION: &str = "2.0";
+
+/// Conversion factor between miles per hour and meters per second
+const MPH_TO_METERS_PER_SECOND: f32 = 0.44704;
+
+/// Protocol buffer messages from the GTFS-Realtime specification
+///
+/// Only the subset of `gtfs-realtime.proto` needed to describe trip updates
+/// and vehicle positions is included. The field names and tags match the
+/// official schema so that the encoded messages can be decoded by any
+/// GTFS-Realtime consumer.
+pub mod transit_realtime {
+    /// The contents of a feed message
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FeedMessage {
+        /// Metadata about this feed and feed message
+        #[prost(message, required, tag = "1")]
+        pub header: FeedHeader,
+
+        /// Contents of the feed
+        #[prost(message, repeated, tag = "2")]
+        pub entity: Vec<FeedEntity>,
+    }
+
+    /// Metadata about a feed, included in feed messages
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FeedHeader {
+        /// Version of the feed specification
+        #[prost(string, required, tag = "1")]
+        pub gtfs_realtime_version: String,
+
+        /// Whether the feed is a full dataset or a differential update
+        #[prost(enumeration = "feed_header::Incrementality", optional, tag = "2")]
+        pub incrementality: Option<i32>,
+
+        /// The moment when the content of this feed was created (POSIX time)
+        #[prost(uint64, optional, tag = "3")]
+        pub timestamp: Option<u64>,
+    }
+
+    /// Nested types of [`FeedHeader`]
+    pub mod feed_header {
+        /// Determines whether the current fetch is incremental
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+        #[repr(i32)]
+        pub enum Incrementality {
+            FullDataset = 0,
+            Differential = 1,
+        }
+    }
+
+    /// A definition (or update) of an entity in the transit feed
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct FeedEntity {
+        /// The ids are used only to provide incrementality support
+        #[prost(string, required, tag = "1")]
+        pub id: String,
+
+        /// Whether this entity is to be deleted
+        #[prost(bool, optional, tag = "2")]
+        pub is_deleted: Option<bool>,
+
+        /// Realtime update of the progress of a vehicle along a trip
+        #[prost(message, optional, tag = "3")]
+        pub trip_update: Option<TripUpdate>,
+
+        /// Realtime positioning information for a given vehicle
+        #[prost(message, optional, tag = "4")]
+        pub vehicle: Option<VehiclePosition>,
+    }
+
+    /// Realtime update of the progress of a vehicle along a trip
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TripUpdate {
+        /// The trip that this message applies to
+        #[prost(message, required, tag = "1")]
+        pub trip: TripDescriptor,
+
+        /// Additional information on the vehicle that is serving this trip
+        #[prost(message, optional, tag = "3")]
+        pub vehicle: Option<VehicleDescriptor>,
+
+        /// Updates to stop times for the trip
+        #[prost(message, repeated, tag = "2")]
+        pub stop_time_update: Vec<StopTimeUpdate>,
+
+        /// The most recent moment at which the vehicle's real-time progress
+        /// was measured (POSIX time)
+        #[prost(uint64, optional, tag = "4")]
+        pub timestamp: Option<u64>,
+
+        /// The current schedule deviation for the trip in seconds
+        #[prost(int32, optional, tag = "5")]
+        pub delay: Option<i32>,
+    }
+
+    /// Timing information for a single predicted event (arrival or departure)
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StopTimeEvent {
+        /// Delay (in seconds) relative to the schedule
+        #[prost(int32, optional, tag = "1")]
+        pub delay: Option<i32>,
+
+        /// Event as absolute time (POSIX time)
+        #[prost(int64, optional, tag = "2")]
+        pub time: Option<i64>,
+
+        /// The uncertainty of the prediction in seconds
+        #[prost(int32, optional, tag = "3")]
+        pub uncertainty: Option<i32>,
+    }
+
+    /// Realtime update for arrival and/or departure events for a given stop
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StopTimeUpdate {
+        /// Must be the same as in stop_times.txt in the corresponding GTFS
+        /// feed
+        #[prost(uint32, optional, tag = "1")]
+        pub stop_sequence: Option<u32>,
+
+        /// Must be the same as in stops.txt in the corresponding GTFS feed
+        #[prost(string, optional, tag = "4")]
+        pub stop_id: Option<String>,
+
+        /// The predicted arrival at this stop
+        #[prost(message, optional, tag = "2")]
+        pub arrival: Option<StopTimeEvent>,
+
+        /// The predicted departure from this stop
+        #[prost(message, optional, tag = "3")]
+        pub departure: Option<StopTimeEvent>,
+
+        /// The relation between this stop time and the static schedule
+        #[prost(
+            enumeration = "stop_time_update::ScheduleRelationship",
+            optional,
+            tag = "5"
+        )]
+        pub schedule_relationship: Option<i32>,
+    }
+
+    /// Nested types of [`StopTimeUpdate`]
+    pub mod stop_time_update {
+        /// The relation between a stop time and the static schedule
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+        #[repr(i32)]
+        pub enum ScheduleRelationship {
+            Scheduled = 0,
+            Skipped = 1,
+            NoData = 2,
+        }
+    }
+
+    /// Realtime positioning information for a given vehicle
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VehiclePosition {
+        /// The trip that this vehicle is serving
+        #[prost(message, optional, tag = "1")]
+        pub trip: Option<TripDescriptor>,
+
+        /// Additional information on the vehicle that is serving this trip
+        #[prost(message, optional, tag = "8")]
+        pub vehicle: Option<VehicleDescriptor>,
+
+        /// Current position of this vehicle
+        #[prost(message, optional, tag = "2")]
+        pub position: Option<Position>,
+
+        /// The stop sequence index of the current stop
+        #[prost(uint32, optional, tag = "3")]
+        pub current_stop_sequence: Option<u32>,
+
+        /// Identifies the current stop
+        #[prost(string, optional, tag = "7")]
+        pub stop_id: Option<String>,
+
+        /// The exact status of the vehicle with respect to the current stop
+        #[prost(
+            enumeration = "vehicle_position::VehicleStopStatus",
+            optional,
+            tag = "4"
+        )]
+        pub current_status: Option<i32>,
+
+        /// Moment at which the vehicle's position was measured (POSIX time)
+        #[prost(uint64, optional, tag = "5")]
+        pub timestamp: Option<u64>,
+    }
+
+    /// Nested types of [`VehiclePosition`]
+    pub mod vehicle_position {
+        /// The status of a vehicle with respect to its current stop
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+        #[repr(i32)]
+        pub enum VehicleStopStatus {
+            IncomingAt = 0,
+            StoppedAt = 1,
+            InTransitTo = 2,
+        }
+    }
+
+    /// A descriptor that identifies a single instance of a GTFS trip
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TripDescriptor {
+        /// The trip_id from the GTFS feed that this selector refers to
+        #[prost(string, optional, tag = "1")]
+        pub trip_id: Option<String>,
+
+        /// The route_id from the GTFS feed that this selector refers to
+        #[prost(string, optional, tag = "5")]
+        pub route_id: Option<String>,
+
+        /// The initially scheduled start time of this trip instance
+        /// (`HH:MM:SS`)
+        #[prost(string, optional, tag = "2")]
+        pub start_time: Option<String>,
+
+        /// The start date of this trip instance (`YYYYMMDD`)
+        #[prost(string, optional, tag = "3")]
+        pub start_date: Option<String>,
+    }
+
+    /// Identification information for the vehicle performing the trip
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct VehicleDescriptor {
+        /// Internal system identification of the vehicle
+        #[prost(string, optional, tag = "1")]
+        pub id: Option<String>,
+
+        /// User visible label
+        #[prost(string, optional, tag = "2")]
+        pub label: Option<String>,
+    }
+
+    /// A geographic position of a vehicle
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Position {
+        /// Degrees North, in the WGS-84 coordinate system
+        #[prost(float, required, tag = "1")]
+        pub latitude: f32,
+
+        /// Degrees East, in the WGS-84 coordinate system
+        #[prost(float, required, tag = "2")]
+        pub longitude: f32,
+
+        /// Bearing in degrees, clockwise from North
+        #[prost(float, optional, tag = "3")]
+        pub bearing: Option<f32>,
+
+        /// Momentary speed measured by the vehicle, in meters per second
+        #[prost(float, optional, tag = "5")]
+        pub speed: Option<f32>,
+    }
+}
+
+/// Maps a [`Train`] to an identifier used in the feed
+type TrainMapper = Box<dyn Fn(&Train) -> String + Send + Sync>;
+
+/// Maps a [`TrainStation`] to an identifier used in the feed
+type StationMapper = Box<dyn Fn(&TrainStation) -> String + Send + Sync>;
+
+/// Resolves the trip of a [`Train`] in a static GTFS feed
+type StaticTripResolver = Box<dyn Fn(&Train) -> Option<StaticTrip> + Send + Sync>;
+
+/// The trip a train is running in a static GTFS feed, see
+/// [`FeedOptions::with_static_trip`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticTrip {
+    /// The GTFS `trip_id` of the trip
+    pub trip_id: String,
+
+    /// The GTFS `route_id` of the trip
+    pub route_id: String,
+
+    /// The GTFS `stop_sequence` of each of the train's [`stations`], in the
+    /// same order. An entry is `None` when the station is not part of the
+    /// trip.
+    ///
+    /// [`stations`]: Train::stations
+    pub stop_sequences: Vec<Option<u32>>,
+}
+
+/// Options used to convert a [`TrainResponse`] into a [`FeedMessage`]
+///
+/// # Example
+///
+/// ```rust
+/// use amtrak_api::{gtfs_rt::FeedOptions, responses::TrainResponse};
+/// use chrono::Utc;
+/// use std::collections::HashMap;
+///
+/// let options = FeedOptions::new()
+///     .with_trip_id(|train| format!("AMTK-{}", train.train_num))
+///     .with_stop_id(|station| station.code.to_lowercase());
+///
+/// let feed = options.feed_message(&TrainResponse(HashMap::new()), Utc::now());
+/// assert!(feed.entity.is_empty());
+/// ```
+pub struct FeedOptions {
+    trip_id: TrainMapper,
+    route_id: TrainMapper,
+    stop_id: StationMapper,
+    static_trip: StaticTripResolver,
+}
+
+impl Default for FeedOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FeedOptions {
+    /// Creates new options that use the identifiers provided by the API
+    pub fn new() -> Self {
+        Self {
+            trip_id: Box::new(|train| train.train_id.clone()),
+            route_id: Box::new(|train| train.route_name.clone()),
+            stop_id: Box::new(|station| station.code.clone()),
+            static_trip: Box::new(|_| None),
+        }
+    }
+
+    /// Sets the function used to derive the GTFS `trip_id` of a train
+    pub fn with_trip_id<F>(mut self, trip_id: F) -> Self
+    where
+        F: Fn(&Train) -> String + Send + Sync + 'static,
+    {
+        self.trip_id = Box::new(trip_id);
+        self
+    }
+
+    /// Sets the function used to derive the GTFS `route_id` of a train
+    pub fn with_route_id<F>(mut self, route_id: F) -> Self
+    where
+        F: Fn(&Train) -> String + Send + Sync + 'static,
+    {
+        self.route_id = Box::new(route_id);
+        self
+    }
+
+    /// Sets the function used to derive the GTFS `stop_id` of a station
+    pub fn with_stop_id<F>(mut self, stop_id: F) -> Self
+    where
+        F: Fn(&TrainStation) -> String + Send + Sync + 'static,
+    {
+        self.stop_id = Box::new(stop_id);
+        self
+    }
+
+    /// Sets the function used to resolve the trip of a train in a static
+    /// GTFS feed, it is called once per train and feed.
+    ///
+    /// The `trip_id` and `route_id` of a resolved trip take precedence over
+    /// [`with_trip_id`] and [`with_route_id`], and its stop sequences are
+    /// the only source of `stop_sequence` in the feed.
+    ///
+    /// [`with_trip_id`]: Self::with_trip_id
+    /// [`with_route_id`]: Self::with_route_id
+    pub fn with_static_trip<F>(mut self, static_trip: F) -> Self
+    where
+        F: Fn(&Train) -> Option<StaticTrip> + Send + Sync + 'static,
+    {
+        self.static_trip = Box::new(static_trip);
+        self
+    }
+
+    /// Converts the trains in `response` into a full dataset [`FeedMessage`]
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The trains to include in the feed.
+    /// * `timestamp` - The moment the feed was created, used in the header.
+    ///
+    /// Entities are sorted by [`train_id`] so that the same response always
+    /// produces the same feed.
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn feed_message<Tz: TimeZone>(
+        &self,
+        response: &TrainResponse,
+        timestamp: DateTime<Tz>,
+    ) -> FeedMessage {
+        let mut trains: Vec<_> = response.iter_trains().collect();
+        trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+
+        FeedMessage {
+            header: FeedHeader {
+                gtfs_realtime_version: GTFS_REALTIME_VERSION.to_string(),
+                incrementality: Some(feed_header::Incrementality::FullDataset as i32),
+                timestamp: Some(timestamp.timestamp() as u64),
+            },
+            entity: trains
+                .into_iter()
+                .flat_map(|train| {
+                    let static_trip = (self.static_trip)(train);
+                    let trip = self.trip_descriptor(train, static_trip.as_ref());
+
+                    [
+                        FeedEntity {
+                            id: format!("trip-{}", train.train_id),
+                            trip_update: Some(self.trip_update(
+                                train,
+                                trip.clone(),
+                                static_trip.as_ref(),
+                            )),
+                            ..Default::default()
+                        },
+                        FeedEntity {
+                            id: format!("vehicle-{}", train.train_id),
+                            vehicle: Some(self.vehicle_position(train, trip, static_trip.as_ref())),
+                            ..Default::default()
+                        },
+                    ]
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds the trip descriptor of a train, the start of the trip is the
+    /// scheduled departure from the origin station in the origin's timezone.
+    fn trip_descriptor(&self, train: &Train, static_trip: Option<&StaticTrip>) -> TripDescriptor {
+        let origin_departure = train
+            .stations
+            .first()
+            .map(|station| station.schedule_departure);
+
+        TripDescriptor {
+            trip_id: Some(match static_trip {
+                Some(static_trip) => static_trip.trip_id.clone(),
+                None => (self.trip_id)(train),
+            }),
+            route_id: Some(match static_trip {
+                Some(static_trip) => static_trip.route_id.clone(),
+                None => (self.route_id)(train),
+            }),
+            start_time: origin_departure.map(|departure| departure.format("%H:%M:%S").to_string()),
+            start_date: origin_departure.map(|departure| departure.format("%Y%m%d").to_string()),
+        }
+    }
+
+    fn vehicle_descriptor(&self, train: &Train) -> VehicleDescriptor {
+        VehicleDescriptor {
+            id: Some(train.train_id.clone()),
+            label: Some(train.train_num.to_string()),
+        }
+    }
+
+    fn trip_update(
+        &self,
+        train: &Train,
+        trip: TripDescriptor,
+        static_trip: Option<&StaticTrip>,
+    ) -> TripUpdate {
+        TripUpdate {
+            trip,
+            vehicle: Some(self.vehicle_descriptor(train)),
+            stop_time_update: train
+                .stations
+                .iter()
+                .enumerate()
+                .map(|(index, station)| {
+                    self.stop_time_update(station, stop_sequence(static_trip, index))
+                })
+                .collect(),
+            timestamp: Some(train.updated_at.timestamp() as u64),
+            delay: train.delay().map(|delay| delay.num_seconds() as i32),
+        }
+    }
+
+    /// Builds the update of a single stop
+    ///
+    /// The API does not provide the GTFS `stop_sequence` of a station, the
+    /// position of the station in the train's route does not match it when
+    /// the train skips stops of the static trip. Without a static trip stops
+    /// are only identified by their `stop_id`.
+    fn stop_time_update(
+        &self,
+        station: &TrainStation,
+        stop_sequence: Option<u32>,
+    ) -> StopTimeUpdate {
+        let arrival = station.arrival.map(|arrival| StopTimeEvent {
+            delay: Some((arrival - station.schedule_arrival).num_seconds() as i32),
+            time: Some(arrival.timestamp()),
+            ..Default::default()
+        });
+        let departure = station.departure.map(|departure| StopTimeEvent {
+            delay: Some((departure - station.schedule_departure).num_seconds() as i32),
+            time: Some(departure.timestamp()),
+            ..Default::default()
+        });
+
+        let schedule_relationship = if arrival.is_none() && departure.is_none() {
+            stop_time_update::ScheduleRelationship::NoData
+        } else {
+            stop_time_update::ScheduleRelationship::Scheduled
+        };
+
+        StopTimeUpdate {
+            stop_sequence,
+            stop_id: Some((self.stop_id)(station)),
+            arrival,
+            departure,
+            schedule_relationship: Some(schedule_relationship as i32),
+        }
+    }
+
+    fn vehicle_position(
+        &self,
+        train: &Train,
+        trip: TripDescriptor,
+        static_trip: Option<&StaticTrip>,
+    ) -> VehiclePosition {
+        // The current stop is either the station the train is stopped at or
+        // the next station the train is heading towards
+        let current_stop = train
+            .stations
+            .iter()
+            .enumerate()
+            .find(|(_, station)| station.status != TrainStatus::Departed);
+
+        let current_status = current_stop.map(|(_, station)| match station.status {
+            TrainStatus::Station => vehicle_position::VehicleStopStatus::StoppedAt,
+            _ => vehicle_position::VehicleStopStatus::InTransitTo,
+        });
+
+        VehiclePosition {
+            trip: Some(trip),
+            vehicle: Some(self.vehicle_descriptor(train)),
+            position: Some(Position {
+                latitude: train.lat as f32,
+                longitude: train.lon as f32,
+                bearing: Some(train.heading.bearing() as f32),
+                speed: Some(train.velocity * MPH_TO_METERS_PER_SECOND),
+            }),
+            current_stop_sequence: current_stop
+                .and_then(|(index, _)| stop_sequence(static_trip, index)),
+            stop_id: current_stop.map(|(_, station)| (self.stop_id)(station)),
+            current_status: current_status.map(|status| status as i32),
+            timestamp: Some(train.last_value.timestamp() as u64),
+        }
+    }
+}
+
+/// Returns the static `stop_sequence` of the station at `index` in the
+/// train's route
+fn stop_sequence(static_trip: Option<&StaticTrip>, index: usize) -> Option<u32> {
+    static_trip?.stop_sequences.get(index).copied().flatten()
+}
+
+impl TrainResponse {
+    /// Converts this response into a GTFS-Realtime [`FeedMessage`] using the
+    /// default [`FeedOptions`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use amtrak_api::Client;
+    /// use chrono::Utc;
+    /// use prost::Message;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let feed = Client::new().trains().await?.to_gtfs_realtime(Utc::now());
+    ///     std::fs::write("amtrak.pb", feed.encode_to_vec())?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_gtfs_realtime<Tz: TimeZone>(&self, timestamp: DateTime<Tz>) -> FeedMessage {
+        FeedOptions::new().feed_message(self, timestamp)
+    }
+}
@@ -0,0 +1,119 @@
+//! Constant time lookups into a [`TrainResponse`]
+//!
+//! [`TrainResponse`] is keyed by train number, so finding a train by its
+//! [`train_id`] or every train of a route walks the whole response. A
+//! [`TrainIndex`] is built once per response and answers those lookups in
+//! constant time while borrowing the trains.
+//!
+//! Like the rest of the crate, routes and stations are looked up ignoring
+//! case: `index.route("keystone")` returns the trains of the `Keystone`.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::Client;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let response = Client::new().trains().await?;
+//!     let index = response.index();
+//!
+//!     for train in index.station("PHL") {
+//!         println!("{} stops in Philadelphia", train.train_id);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`train_id`]: Train::train_id
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::responses::{Train, TrainResponse};
+
+/// A secondary index over the trains of a [`TrainResponse`]
+#[derive(Debug, Clone)]
+pub struct TrainIndex<'a> {
+    by_id: HashMap<&'a str, &'a Train>,
+    by_route: HashMap<String, Vec<&'a Train>>,
+    by_station: HashMap<String, Vec<&'a Train>>,
+}
+
+impl<'a> TrainIndex<'a> {
+    /// Indexes every train of the response
+    pub fn new(response: &'a TrainResponse) -> Self {
+        Self {
+            by_id: response
+                .iter_trains()
+                .map(|train| (train.train_id.as_str(), train))
+                .collect(),
+            by_route: normalize(response.by_route()),
+            by_station: normalize(response.by_station()),
+        }
+    }
+
+    /// Returns the train with the provided [`train_id`]
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn get(&self, train_id: &str) -> Option<&'a Train> {
+        self.by_id.get(train_id).copied()
+    }
+
+    /// Returns the trains of the provided route sorted by [`train_id`],
+    /// ignoring case
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn route(&self, route_name: &str) -> &[&'a Train] {
+        self.by_route
+            .get(&route_name.to_ascii_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the trains stopping at the provided station sorted by
+    /// [`train_id`], ignoring case
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn station(&self, station_code: &str) -> &[&'a Train] {
+        self.by_station
+            .get(&station_code.to_ascii_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of indexed trains
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    /// Returns `true` if no train was indexed
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+}
+
+/// Lowercases the keys of the groups, merging the groups whose keys only
+/// differ by case
+fn normalize<'a>(groups: BTreeMap<&str, Vec<&'a Train>>) -> HashMap<String, Vec<&'a Train>> {
+    let mut normalized: HashMap<String, Vec<&Train>> = HashMap::new();
+    for (key, trains) in groups {
+        normalized
+            .entry(key.to_ascii_lowercase())
+            .or_default()
+            .extend(trains);
+    }
+    for trains in normalized.values_mut() {
+        trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+        trains.dedup_by(|a, b| a.train_id == b.train_id);
+    }
+
+    normalized
+}
+
+impl TrainResponse {
+    /// Builds a [`TrainIndex`] over the trains of this response
+    pub fn index(&self) -> TrainIndex<'_> {
+        TrainIndex::new(self)
+    }
+}
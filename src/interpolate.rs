@@ -0,0 +1,151 @@
+//! Position interpolation
+//!
+//! The [`lat`] and [`lon`] of a train are only refreshed every few minutes
+//! (see [`last_value`]), which makes markers jump around on a map. This
+//! module estimates where a train is at any instant after its last report:
+//!
+//! * a train standing at a station (with a [`Station`] status) is clamped to
+//!   the position of that station
+//! * a moving train is advanced at its [`velocity`] towards its next station,
+//!   stopping once it reaches it
+//! * when the next station is unknown the train is advanced along its
+//!   [`heading`] instead (dead reckoning)
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::Client;
+//! use chrono::Utc;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Client::new();
+//!     let stations = client.stations().await?;
+//!     let now = Utc::now().fixed_offset();
+//!
+//!     for train in client.trains().await?.iter_trains() {
+//!         let position = train.position_at(now, &stations);
+//!         println!("{}: {:?}", train.train_id, position.coordinate);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`lat`]: Train::lat
+//! [`lon`]: Train::lon
+//! [`last_value`]: Train::last_value
+//! [`velocity`]: Train::velocity
+//! [`heading`]: Train::heading
+//! [`Station`]: TrainStatus::Station
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::{
+    geo::Coordinate,
+    responses::{StationResponse, Train, TrainStatus},
+};
+
+/// How an [`EstimatedPosition`] was obtained
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PositionSource {
+    /// The last position reported by the API, unchanged
+    Reported,
+
+    /// The position of the station the train is standing at
+    Station,
+
+    /// Advanced along the path towards the next station
+    Path,
+
+    /// Advanced along the heading of the train
+    DeadReckoning,
+}
+
+/// The estimated position of a train at an instant
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EstimatedPosition {
+    /// The estimated coordinate of the train
+    pub coordinate: Coordinate,
+
+    /// How the coordinate was estimated
+    pub source: PositionSource,
+}
+
+impl Train {
+    /// Estimates the position of this train at the provided instant
+    ///
+    /// Station coordinates are looked up in `stations`. Instants before the
+    /// [`last_value`] of the train return the reported position.
+    ///
+    /// [`last_value`]: Self::last_value
+    pub fn position_at(
+        &self,
+        at: DateTime<FixedOffset>,
+        stations: &StationResponse,
+    ) -> EstimatedPosition {
+        let coordinate_of = |code: &str| stations.0.get(code).map(|station| station.coordinate());
+        let next = self.remaining_stations().next();
+
+        if let Some(next) = next.filter(|next| next.status == TrainStatus::Station) {
+            if let Some(coordinate) = coordinate_of(&next.code) {
+                return EstimatedPosition {
+                    coordinate,
+                    source: PositionSource::Station,
+                };
+            }
+        }
+
+        let distance = self.distance_travelled(at);
+        if distance <= 0.0 {
+            return EstimatedPosition {
+                coordinate: self.coordinate(),
+                source: PositionSource::Reported,
+            };
+        }
+
+        match next.and_then(|next| coordinate_of(&next.code)) {
+            Some(target) => {
+                let remaining = self.coordinate().distance_to(target);
+                let coordinate = if distance >= remaining {
+                    target
+                } else {
+                    self.coordinate()
+                        .destination(self.coordinate().bearing_to(target), distance)
+                };
+
+                EstimatedPosition {
+                    coordinate,
+                    source: PositionSource::Path,
+                }
+            }
+            None => EstimatedPosition {
+                coordinate: self.dead_reckon(at),
+                source: PositionSource::DeadReckoning,
+            },
+        }
+    }
+
+    /// Estimates the position of this train at the provided instant by
+    /// advancing its last reported position along its [`heading`] at its
+    /// [`velocity`].
+    ///
+    /// [`heading`]: Self::heading
+    /// [`velocity`]: Self::velocity
+    pub fn dead_reckon(&self, at: DateTime<FixedOffset>) -> Coordinate {
+        let distance = self.distance_travelled(at);
+        if distance <= 0.0 {
+            return self.coordinate();
+        }
+
+        self.coordinate()
+            .destination(self.heading.bearing(), distance)
+    }
+
+    /// Returns the distance in miles covered at the current velocity between
+    /// the last report and the provided instant
+    fn distance_travelled(&self, at: DateTime<FixedOffset>) -> f64 {
+        let hours = (at - self.last_value).num_milliseconds() as f64 / 3_600_000.0;
+        hours.max(0.0) * self.velocity as f64
+    }
+}
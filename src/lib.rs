@@ -14,7 +14,7 @@
 //! Here is an example of querying trains that have stopped at or will stop at a
 //! specific station.
 //!
-//! ```rust
+//! ```rust,no_run
 //! use amtrak_api::Client;
 //!
 //! const STATION_CODE: &str = "PHL";
@@ -38,8 +38,39 @@
 //! }
 //! ```
 
+pub mod address;
+#[cfg(feature = "sqlite")]
+pub mod analytics;
+pub mod board;
 pub mod client;
+pub mod connection;
+#[cfg(feature = "bundled-stations")]
+pub mod directory;
 pub mod errors;
+pub mod events;
+pub mod export;
+pub mod freshness;
+pub mod geo;
+pub mod geojson;
+#[cfg(feature = "gtfs")]
+pub mod gtfs;
+#[cfg(feature = "gtfs-rt")]
+pub mod gtfs_rt;
+pub mod index;
+pub mod interpolate;
+pub mod merge;
+pub mod metrics;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod planner;
+pub mod predict;
+pub mod query;
 pub mod responses;
+pub mod search;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shape;
+#[cfg(feature = "sqlite")]
+pub mod storage;
 
 pub use client::Client;
@@ -0,0 +1,186 @@
+//! Merging overlapping responses
+//!
+//! Combining the result of [`Client::trains`] with several
+//! [`Client::train`] lookups, or polling the API from several places, yields
+//! [`TrainResponse`]s that share some trains. [`TrainResponse::merge`]
+//! combines them, keeping the most recent version of each train according to
+//! its [`updated_at`], and reports the stations where both versions
+//! contradict each other.
+//!
+//! A station is contradictory when the kept version is behind the discarded
+//! one (for example [`Enroute`] while the other version already [`Departed`])
+//! or when both versions were updated at the same instant but disagree.
+//!
+//! [`Client::trains`]: crate::Client::trains
+//! [`Client::train`]: crate::Client::train
+//! [`updated_at`]: Train::updated_at
+//! [`Enroute`]: TrainStatus::Enroute
+//! [`Departed`]: TrainStatus::Departed
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::responses::{Train, TrainResponse, TrainStatus};
+
+/// Two versions of a train disagreeing on the status of a station
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Conflict {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The code of the station
+    pub station_code: String,
+
+    /// The status of the station in the version that was kept
+    pub kept_status: TrainStatus,
+
+    /// The [`updated_at`] of the version that was kept
+    ///
+    /// [`updated_at`]: Train::updated_at
+    pub kept_updated_at: DateTime<FixedOffset>,
+
+    /// The status of the station in the version that was discarded
+    pub discarded_status: TrainStatus,
+
+    /// The [`updated_at`] of the version that was discarded
+    ///
+    /// [`updated_at`]: Train::updated_at
+    pub discarded_updated_at: DateTime<FixedOffset>,
+}
+
+/// Orders the statuses in the sequence a train goes through them
+fn progress(status: TrainStatus) -> Option<u8> {
+    match status {
+        TrainStatus::Enroute => Some(0),
+        TrainStatus::Station => Some(1),
+        TrainStatus::Departed => Some(2),
+        TrainStatus::Unknown => None,
+    }
+}
+
+fn conflicts(kept: &Train, discarded: &Train) -> Vec<Conflict> {
+    let discarded_statuses: HashMap<_, _> = discarded
+        .stations
+        .iter()
+        .map(|station| (station.code.as_str(), station.status))
+        .collect();
+
+    kept.stations
+        .iter()
+        .filter_map(|station| {
+            let discarded_status = *discarded_statuses.get(station.code.as_str())?;
+            let contradicts = if kept.updated_at == discarded.updated_at {
+                station.status != discarded_status
+            } else {
+                match (progress(station.status), progress(discarded_status)) {
+                    (Some(kept), Some(discarded)) => kept < discarded,
+                    _ => false,
+                }
+            };
+
+            contradicts.then(|| Conflict {
+                train_id: kept.train_id.clone(),
+                station_code: station.code.clone(),
+                kept_status: station.status,
+                kept_updated_at: kept.updated_at,
+                discarded_status,
+                discarded_updated_at: discarded.updated_at,
+            })
+        })
+        .collect()
+}
+
+impl TrainResponse {
+    /// Merges the trains of `other` into this response and returns the
+    /// conflicts that were found, grouped by [`train_id`] in the order the
+    /// train stops at the stations
+    ///
+    /// Trains are matched on their [`train_id`] and the version with the
+    /// newest [`updated_at`] is kept, the version already in this response
+    /// wins ties.
+    ///
+    /// [`train_id`]: Train::train_id
+    /// [`updated_at`]: Train::updated_at
+    pub fn merge(&mut self, other: TrainResponse) -> Vec<Conflict> {
+        let mut conflicts_found = Vec::new();
+
+        // Where each train is stored, built once so matching stays linear
+        let mut positions: HashMap<String, (String, usize)> =
+            self.0
+                .iter()
+                .flat_map(|(key, trains)| {
+                    trains.iter().enumerate().map(move |(position, train)| {
+                        (train.train_id.clone(), (key.clone(), position))
+                    })
+                })
+                .collect();
+        // Trains kept from `other` under another key, by train id: the key
+        // they are stored under and the key they move to
+        let mut relocated: HashMap<String, (String, String)> = HashMap::new();
+
+        for (key, trains) in other.0 {
+            for train in trains {
+                let Some((existing_key, position)) = positions.get(&train.train_id).cloned() else {
+                    let trains = self.0.entry(key.clone()).or_default();
+                    positions.insert(train.train_id.clone(), (key.clone(), trains.len()));
+                    trains.push(train);
+                    continue;
+                };
+
+                let existing = &mut self.0.get_mut(&existing_key).unwrap()[position];
+                if train.updated_at <= existing.updated_at {
+                    conflicts_found.extend(conflicts(existing, &train));
+                    continue;
+                }
+
+                conflicts_found.extend(conflicts(&train, existing));
+                relocated
+                    .entry(train.train_id.clone())
+                    .or_insert_with(|| (existing_key.clone(), key.clone()))
+                    .1 = key.clone();
+                *existing = train;
+            }
+        }
+
+        // Moving trains shifts the positions, so it only happens once every
+        // train was matched
+        for (train_id, (existing_key, key)) in relocated {
+            if existing_key == key {
+                continue;
+            }
+
+            let trains = self.0.get_mut(&existing_key).unwrap();
+            let position = trains
+                .iter()
+                .position(|train| train.train_id == train_id)
+                .unwrap();
+            let train = trains.remove(position);
+            if trains.is_empty() {
+                self.0.remove(&existing_key);
+            }
+            self.0.entry(key).or_default().push(train);
+        }
+
+        conflicts_found.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+        conflicts_found
+    }
+
+    /// Merges every response into a single one, see [`TrainResponse::merge`]
+    pub fn merge_all<I>(responses: I) -> (TrainResponse, Vec<Conflict>)
+    where
+        I: IntoIterator<Item = TrainResponse>,
+    {
+        let mut merged = TrainResponse(HashMap::new());
+        let mut conflicts = Vec::new();
+        for response in responses {
+            conflicts.extend(merged.merge(response));
+        }
+        conflicts.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+
+        (merged, conflicts)
+    }
+}
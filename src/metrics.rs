@@ -0,0 +1,382 @@
+//! Prometheus metrics
+//!
+//! This module exposes two sets of metrics in the Prometheus text exposition
+//! format:
+//!
+//! * [`NetworkMetrics`] describe the health of the Amtrak network itself (how
+//!   many trains are running, how late they are and how old their data is)
+//!   and are computed from a [`TrainResponse`].
+//! * [`ClientMetrics`] describe our own ingestion (request latency, errors
+//!   and cache hits) and are recorded by every [`Client`].
+//!
+//! Both implement [`Display`] which renders the metrics ready to be served on
+//! a `/metrics` endpoint.
+//!
+//! [`Client`]: crate::Client
+//! [`Display`]: std::fmt::Display
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, TimeZone};
+
+use crate::{
+    errors::Error,
+    responses::{TrainResponse, TrainState},
+};
+
+/// Upper bounds (in seconds) of the request latency histogram buckets
+const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Gauges describing the state of the Amtrak network at an instant
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkMetrics {
+    /// The number of [`Active`] trains for each route
+    ///
+    /// [`Active`]: TrainState::Active
+    pub active_trains_per_route: BTreeMap<String, u64>,
+
+    /// The average delay in minutes of the [`Active`] trains of each route,
+    /// trains with an unknown delay are left out.
+    ///
+    /// [`Active`]: TrainState::Active
+    pub average_delay_per_route: BTreeMap<String, f64>,
+
+    /// The number of trains in each [`TrainState`]
+    pub trains_per_state: BTreeMap<String, u64>,
+
+    /// The number of seconds since the [`last_value`] of each train, keyed by
+    /// [`train_id`]
+    ///
+    /// [`last_value`]: crate::responses::Train::last_value
+    /// [`train_id`]: crate::responses::Train::train_id
+    pub data_age_seconds: BTreeMap<String, i64>,
+}
+
+impl TrainResponse {
+    /// Returns the [`NetworkMetrics`] of the trains in this response, using
+    /// `now` to compute the age of their data.
+    pub fn network_metrics<Tz: TimeZone>(&self, now: DateTime<Tz>) -> NetworkMetrics {
+        let mut metrics = NetworkMetrics {
+            active_trains_per_route: BTreeMap::new(),
+            average_delay_per_route: BTreeMap::new(),
+            trains_per_state: [
+                TrainState::Predeparture,
+                TrainState::Active,
+                TrainState::Completed,
+            ]
+            .iter()
+            .map(|state| (format!("{:?}", state), 0))
+            .collect(),
+            data_age_seconds: BTreeMap::new(),
+        };
+        let mut delays: BTreeMap<&str, (i64, u32)> = BTreeMap::new();
+
+        for train in self.iter_trains() {
+            *metrics
+                .trains_per_state
+                .entry(format!("{:?}", train.train_state))
+                .or_default() += 1;
+            metrics.data_age_seconds.insert(
+                train.train_id.clone(),
+                (now.clone().fixed_offset() - train.last_value).num_seconds(),
+            );
+
+            if train.train_state != TrainState::Active {
+                continue;
+            }
+
+            *metrics
+                .active_trains_per_route
+                .entry(train.route_name.clone())
+                .or_default() += 1;
+
+            if let Some(delay) = train.delay() {
+                let (sum, count) = delays.entry(&train.route_name).or_default();
+                *sum += delay.num_minutes();
+                *count += 1;
+            }
+        }
+
+        metrics.average_delay_per_route = delays
+            .into_iter()
+            .map(|(route, (sum, count))| (route.to_string(), sum as f64 / count as f64))
+            .collect();
+
+        metrics
+    }
+}
+
+impl fmt::Display for NetworkMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_header(
+            f,
+            "amtrak_active_trains",
+            "gauge",
+            "Number of active trains per route",
+        )?;
+        for (route, count) in &self.active_trains_per_route {
+            writeln!(
+                f,
+                "amtrak_active_trains{{route=\"{}\"}} {}",
+                escape(route),
+                count
+            )?;
+        }
+
+        write_header(
+            f,
+            "amtrak_average_delay_minutes",
+            "gauge",
+            "Average delay in minutes of the active trains per route",
+        )?;
+        for (route, delay) in &self.average_delay_per_route {
+            writeln!(
+                f,
+                "amtrak_average_delay_minutes{{route=\"{}\"}} {}",
+                escape(route),
+                delay
+            )?;
+        }
+
+        write_header(f, "amtrak_trains", "gauge", "Number of trains per state")?;
+        for (state, count) in &self.trains_per_state {
+            writeln!(f, "amtrak_trains{{state=\"{}\"}} {}", state, count)?;
+        }
+
+        write_header(
+            f,
+            "amtrak_train_data_age_seconds",
+            "gauge",
+            "Seconds since the last position update of each train",
+        )?;
+        for (train_id, age) in &self.data_age_seconds {
+            writeln!(
+                f,
+                "amtrak_train_data_age_seconds{{train_id=\"{}\"}} {}",
+                escape(train_id),
+                age
+            )?;
+        }
+
+        write_header(
+            f,
+            "amtrak_data_age_seconds_max",
+            "gauge",
+            "Seconds since the least recent position update of any train",
+        )?;
+        writeln!(
+            f,
+            "amtrak_data_age_seconds_max {}",
+            self.data_age_seconds.values().max().copied().unwrap_or(0)
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if value <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+/// Metrics recorded while talking to the Amtrak API
+///
+/// Every [`Client`] records into its own instance which is shared between its
+/// clones, see [`Client::metrics`].
+///
+/// [`Client`]: crate::Client
+/// [`Client::metrics`]: crate::Client::metrics
+#[derive(Debug, Default)]
+pub struct ClientMetrics {
+    latencies: Mutex<BTreeMap<&'static str, Histogram>>,
+    errors: Mutex<BTreeMap<&'static str, u64>>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+impl ClientMetrics {
+    /// Creates a new instance with every metric at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the latency of a request made to the provided endpoint (for
+    /// example `trains`)
+    pub fn record_request(&self, endpoint: &'static str, latency: Duration) {
+        self.latencies
+            .lock()
+            .unwrap()
+            .entry(endpoint)
+            .or_default()
+            .observe(latency.as_secs_f64());
+    }
+
+    /// Records an error returned to the caller
+    pub fn record_error(&self, error: &Error) {
+        *self
+            .errors
+            .lock()
+            .unwrap()
+            .entry(error_variant(error))
+            .or_default() += 1;
+    }
+
+    /// Records a lookup served from a cache instead of the API
+    pub fn record_cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a lookup that could not be served from a cache
+    pub fn record_cache_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the number of requests made to the provided endpoint
+    pub fn requests(&self, endpoint: &str) -> u64 {
+        self.latencies
+            .lock()
+            .unwrap()
+            .get(endpoint)
+            .map_or(0, |histogram| histogram.count)
+    }
+
+    /// Returns the number of errors of the provided [`Error`] variant (for
+    /// example `RequestFailed`)
+    pub fn errors(&self, variant: &str) -> u64 {
+        self.errors
+            .lock()
+            .unwrap()
+            .get(variant)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of cache hits
+    pub fn cache_hits(&self) -> u64 {
+        self.cache_hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cache misses
+    pub fn cache_misses(&self) -> u64 {
+        self.cache_misses.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Display for ClientMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_header(
+            f,
+            "amtrak_client_request_duration_seconds",
+            "histogram",
+            "Latency of the requests made to the Amtrak API per endpoint",
+        )?;
+        for (endpoint, histogram) in self.latencies.lock().unwrap().iter() {
+            for (bucket, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                writeln!(
+                    f,
+                    "amtrak_client_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                    endpoint, bound, bucket
+                )?;
+            }
+            writeln!(
+                f,
+                "amtrak_client_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+                endpoint, histogram.count
+            )?;
+            writeln!(
+                f,
+                "amtrak_client_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.sum
+            )?;
+            writeln!(
+                f,
+                "amtrak_client_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.count
+            )?;
+        }
+
+        write_header(
+            f,
+            "amtrak_client_errors_total",
+            "counter",
+            "Number of errors returned by the client per error variant",
+        )?;
+        for (variant, count) in self.errors.lock().unwrap().iter() {
+            writeln!(
+                f,
+                "amtrak_client_errors_total{{variant=\"{}\"}} {}",
+                variant, count
+            )?;
+        }
+
+        write_header(
+            f,
+            "amtrak_cache_hits_total",
+            "counter",
+            "Number of lookups served from the cache",
+        )?;
+        writeln!(f, "amtrak_cache_hits_total {}", self.cache_hits())?;
+
+        write_header(
+            f,
+            "amtrak_cache_misses_total",
+            "counter",
+            "Number of lookups that could not be served from the cache",
+        )?;
+        writeln!(f, "amtrak_cache_misses_total {}", self.cache_misses())
+    }
+}
+
+fn write_header(f: &mut fmt::Formatter, name: &str, kind: &str, help: &str) -> fmt::Result {
+    writeln!(f, "# HELP {} {}", name, help)?;
+    writeln!(f, "# TYPE {} {}", name, kind)
+}
+
+/// Escapes a label value as required by the text exposition format
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn error_variant(error: &Error) -> &'static str {
+    match error {
+        // reqwest reports a body that is not valid JSON as a request error
+        Error::RequestFailed(e) if e.is_decode() => "DeserializeFailed",
+        Error::RequestFailed(_) => "RequestFailed",
+        Error::DeserializeFailed(_) => "DeserializeFailed",
+        Error::ApiErrorResponse(_) => "ApiErrorResponse",
+        Error::IoFailed(_) => "IoFailed",
+        #[cfg(feature = "csv")]
+        Error::CsvFailed(_) => "CsvFailed",
+        #[cfg(feature = "gtfs")]
+        Error::ZipFailed(_) => "ZipFailed",
+        #[cfg(feature = "sqlite")]
+        Error::SqliteFailed(_) => "SqliteFailed",
+        #[cfg(feature = "notify")]
+        Error::ConfigFailed(_) => "ConfigFailed",
+        #[cfg(feature = "notify")]
+        Error::InvalidConfig(_) => "InvalidConfig",
+    }
+}
@@ -0,0 +1,482 @@
+//! Notification rules delivered over webhooks
+//!
+//! Rules such as "notify when train 612 is 15+ minutes late at PHL" or
+//! "notify when a train departs NYP" are declared in a TOML file along with
+//! the URL of the webhook receiving the alerts:
+//!
+//! ```toml
+//! webhook = "https://example.com/amtrak"
+//!
+//! [[rules]]
+//! name = "612 late at Philadelphia"
+//! train_num = 612
+//! station = "PHL"
+//! late_minutes = 15
+//!
+//! [[rules]]
+//! name = "Keystone leaving New York"
+//! route = "Keystone"
+//! station = "NYP"
+//! on = "departure"
+//! ```
+//!
+//! A rule may narrow the trains it applies to with `train_num`, `route` and
+//! `station`, and triggers either `on` an `"arrival"` or a `"departure"`
+//! (see [`events::diff`]), when the train is at least `late_minutes` late, or
+//! both. Without a `station` the delay of the train as a whole is used.
+//!
+//! A [`Notifier`] evaluates the rules against successive snapshots. Each
+//! rule alerts at most once per train and station, and every [`Alert`] is
+//! POSTed as JSON to the webhook. A train has to be missing from
+//! [`FORGET_AFTER_SNAPSHOTS`] snapshots in a row before its rules may alert
+//! again, so a train briefly dropped by the API does not alert twice.
+//! Alerts the webhook did not accept are retried by every poll until the
+//! train is forgotten.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{notify::{Config, Notifier}, Client};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Client::new();
+//!     let mut notifier = Notifier::new(Config::load("notify.toml")?);
+//!
+//!     loop {
+//!         for alert in notifier.poll(&client).await? {
+//!             println!("{}", alert.message);
+//!         }
+//!         tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+//!     }
+//! }
+//! ```
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    path::Path,
+};
+
+use chrono::{DateTime, FixedOffset};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::Result,
+    errors::Error,
+    events::{self, TrainEventKind},
+    responses::{Train, TrainResponse},
+    Client,
+};
+
+/// The number of snapshots in a row a train must be missing from before the
+/// alerts already sent for it are forgotten
+pub const FORGET_AFTER_SNAPSHOTS: usize = 3;
+
+/// The event a rule triggers on
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// The train arrived at the station
+    Arrival,
+
+    /// The train departed from the station
+    Departure,
+}
+
+/// A single notification rule
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Rule {
+    /// The name of the rule, repeated in its alerts
+    pub name: String,
+
+    /// Only applies to trains with this [`train_num`]
+    ///
+    /// [`train_num`]: Train::train_num
+    #[serde(default)]
+    pub train_num: Option<u32>,
+
+    /// Only applies to trains of this [`route_name`], ignoring case
+    ///
+    /// [`route_name`]: Train::route_name
+    #[serde(default)]
+    pub route: Option<String>,
+
+    /// Only applies to this station, ignoring case
+    #[serde(default)]
+    pub station: Option<String>,
+
+    /// Triggers when the train arrives at or departs from a station
+    #[serde(default)]
+    pub on: Option<Trigger>,
+
+    /// Triggers when the train is at least this many minutes late
+    #[serde(default)]
+    pub late_minutes: Option<i64>,
+}
+
+/// The rules and where to deliver their alerts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Config {
+    /// The URL every alert is POSTed to
+    #[serde(with = "url_string")]
+    pub webhook: Url,
+
+    /// The rules to evaluate
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+impl Config {
+    /// Parses the configuration from a TOML document
+    ///
+    /// The `webhook` must be a valid URL and every rule must set `on`,
+    /// `late_minutes` or both.
+    pub fn from_toml(document: &str) -> Result<Self> {
+        let config: Self = toml::from_str(document)?;
+
+        if let Some(rule) = config
+            .rules
+            .iter()
+            .find(|rule| rule.on.is_none() && rule.late_minutes.is_none())
+        {
+            return Err(Error::InvalidConfig(format!(
+                "rule \"{}\" sets neither `on` nor `late_minutes`",
+                rule.name
+            )));
+        }
+
+        Ok(config)
+    }
+
+    /// Reads and parses the TOML configuration file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// (De)serializes a URL as a string
+mod url_string {
+    use reqwest::Url;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(url: &Url, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(url.as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Url, D::Error> {
+        let url = String::deserialize(deserializer)?;
+        Url::parse(&url).map_err(|e| D::Error::custom(format!("invalid URL \"{}\": {}", url, e)))
+    }
+}
+
+/// A rule triggered by a train
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Alert {
+    /// The [`name`] of the triggered rule
+    ///
+    /// [`name`]: Rule::name
+    pub rule: String,
+
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// The station the rule triggered at, `None` for a delay of the train as
+    /// a whole
+    pub station_code: Option<String>,
+
+    /// The delay in minutes at the station (or of the train as a whole), a
+    /// negative value means the train is early.
+    pub delay_minutes: Option<i64>,
+
+    /// When the rule triggered according to the API
+    pub timestamp: DateTime<FixedOffset>,
+
+    /// A human readable description of the alert
+    pub message: String,
+}
+
+impl Rule {
+    fn applies_to(&self, train: &Train) -> bool {
+        self.train_num.is_none_or(|num| train.train_num == num)
+            && self
+                .route
+                .as_ref()
+                .is_none_or(|route| train.route_name.eq_ignore_ascii_case(route))
+    }
+
+    fn applies_at(&self, station_code: &str) -> bool {
+        self.station
+            .as_ref()
+            .is_none_or(|station| station.eq_ignore_ascii_case(station_code))
+    }
+
+    fn is_late(&self, delay_minutes: Option<i64>) -> bool {
+        self.late_minutes
+            .is_none_or(|late| delay_minutes.is_some_and(|delay| delay >= late))
+    }
+
+    /// Builds the alert of the train, `event` describes the arrival or
+    /// departure that triggered the rule if any (for example `departed NYP`)
+    fn alert(
+        &self,
+        train: &Train,
+        station_code: Option<&str>,
+        timestamp: DateTime<FixedOffset>,
+        event: Option<String>,
+    ) -> Alert {
+        let delay_minutes = delay_minutes(train, station_code);
+        let late = delay_minutes
+            .filter(|_| self.late_minutes.is_some())
+            .map(|delay| format!("{} minutes late", delay));
+
+        let description = match (event, late, station_code) {
+            (Some(event), Some(late), _) => format!("{}, {}", event, late),
+            (Some(event), None, _) => event,
+            (None, Some(late), Some(code)) => format!("is {} at {}", late, code),
+            (None, Some(late), None) => format!("is {}", late),
+            (None, None, _) => "is running late".to_string(),
+        };
+        let message = format!(
+            "{} {} ({}) {}",
+            train.route_name, train.train_num, train.train_id, description
+        );
+
+        Alert {
+            rule: self.name.clone(),
+            train_id: train.train_id.clone(),
+            train_num: train.train_num,
+            route_name: train.route_name.clone(),
+            station_code: station_code.map(str::to_string),
+            delay_minutes,
+            timestamp,
+            message,
+        }
+    }
+}
+
+/// Returns the arrival delay at the station, or the delay of the train as a
+/// whole without a station
+fn delay_minutes(train: &Train, station_code: Option<&str>) -> Option<i64> {
+    let delay = match station_code {
+        Some(code) => train
+            .stations
+            .iter()
+            .find(|station| station.code == code)?
+            .arrival_delay(),
+        None => train.delay(),
+    };
+
+    delay.map(|delay| delay.num_minutes())
+}
+
+/// Evaluates rules against successive snapshots and delivers the alerts
+#[derive(Debug)]
+pub struct Notifier {
+    config: Config,
+    client: reqwest::Client,
+    previous: Option<TrainResponse>,
+    /// The alerts already sent, by rule index, train id and station, with
+    /// the number of snapshots in a row the train has been missing from
+    sent: HashMap<(usize, String, Option<String>), usize>,
+    pending: Vec<Alert>,
+    last_error: Option<Error>,
+}
+
+impl Notifier {
+    /// Creates a notifier for the provided configuration
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            previous: None,
+            sent: HashMap::new(),
+            pending: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    /// Returns the configuration of this notifier
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the alerts that have not been delivered yet, they are retried
+    /// by the next [`poll`] and dropped once their train is forgotten after
+    /// [`FORGET_AFTER_SNAPSHOTS`] snapshots
+    ///
+    /// [`poll`]: Self::poll
+    pub fn pending(&self) -> &[Alert] {
+        &self.pending
+    }
+
+    /// Returns the error of the last delivery that failed, it is cleared
+    /// once every pending alert has been delivered
+    pub fn last_error(&self) -> Option<&Error> {
+        self.last_error.as_ref()
+    }
+
+    /// Evaluates every rule against the snapshot and returns the new alerts
+    /// without delivering them
+    ///
+    /// Rules triggering on arrivals and departures compare the snapshot with
+    /// the one provided to the previous call, so they never trigger on the
+    /// first snapshot. A rule alerts at most once per train and station
+    /// until the train has been missing from [`FORGET_AFTER_SNAPSHOTS`]
+    /// snapshots in a row.
+    pub fn evaluate(&mut self, snapshot: &TrainResponse) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let trains = snapshot.index();
+
+        let events = self
+            .previous
+            .as_ref()
+            .map(|previous| events::diff(previous, snapshot))
+            .unwrap_or_default();
+
+        for (index, rule) in self.config.rules.iter().enumerate() {
+            let mut candidates = Vec::new();
+
+            match rule.on {
+                Some(trigger) => {
+                    for event in &events {
+                        let description = match (&event.kind, trigger) {
+                            (TrainEventKind::Arrival, Trigger::Arrival) => "arrived at",
+                            (TrainEventKind::Departure, Trigger::Departure) => "departed",
+                            _ => continue,
+                        };
+                        let Some(train) = trains.get(&event.train_id) else {
+                            continue;
+                        };
+
+                        candidates.push(rule.alert(
+                            train,
+                            Some(&event.station_code),
+                            event.timestamp,
+                            Some(format!("{} {}", description, event.station_code)),
+                        ));
+                    }
+                }
+                None => {
+                    for train in snapshot.iter_trains() {
+                        match &rule.station {
+                            Some(_) => candidates.extend(
+                                train
+                                    .stations
+                                    .iter()
+                                    .filter(|station| rule.applies_at(&station.code))
+                                    .map(|station| {
+                                        rule.alert(
+                                            train,
+                                            Some(&station.code),
+                                            train.updated_at,
+                                            None,
+                                        )
+                                    }),
+                            ),
+                            None => {
+                                candidates.push(rule.alert(train, None, train.updated_at, None))
+                            }
+                        }
+                    }
+                }
+            }
+
+            for alert in candidates {
+                let Some(train) = trains.get(&alert.train_id) else {
+                    continue;
+                };
+                let applies = rule.applies_to(train)
+                    && alert
+                        .station_code
+                        .as_deref()
+                        .is_none_or(|code| rule.applies_at(code))
+                    && rule.is_late(alert.delay_minutes);
+                let key = (index, alert.train_id.clone(), alert.station_code.clone());
+
+                if !applies {
+                    continue;
+                }
+                if let Entry::Vacant(entry) = self.sent.entry(key) {
+                    entry.insert(0);
+                    alerts.push(alert);
+                }
+            }
+        }
+
+        // Forget the trains that are gone, along with their undelivered
+        // alerts, so neither the map nor the pending alerts grow forever
+        let mut forgotten = HashSet::new();
+        self.sent.retain(|(_, train_id, _), missing| {
+            if trains.get(train_id).is_some() {
+                *missing = 0;
+            } else {
+                *missing += 1;
+            }
+            if *missing < FORGET_AFTER_SNAPSHOTS {
+                return true;
+            }
+            forgotten.insert(train_id.clone());
+            false
+        });
+        self.pending
+            .retain(|alert| !forgotten.contains(&alert.train_id));
+        self.previous = Some(snapshot.clone());
+
+        alerts
+    }
+
+    /// POSTs the alert as JSON to the webhook
+    pub async fn deliver(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(self.config.webhook.clone())
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Fetches the trains, evaluates the rules and delivers the new alerts,
+    /// returning the alerts that were delivered
+    ///
+    /// Delivery stops at the first alert the webhook does not accept, that
+    /// alert and the following ones are kept in [`pending`] and retried by
+    /// the next call while the error is kept in [`last_error`]. An error is
+    /// only returned when the trains cannot be fetched.
+    ///
+    /// [`pending`]: Self::pending
+    /// [`last_error`]: Self::last_error
+    pub async fn poll(&mut self, client: &Client) -> Result<Vec<Alert>> {
+        let snapshot = client.trains().await?;
+        let alerts = self.evaluate(&snapshot);
+        self.pending.extend(alerts);
+
+        let mut delivered = 0;
+        for alert in &self.pending {
+            if let Err(e) = self.deliver(alert).await {
+                self.last_error = Some(e);
+                break;
+            }
+            delivered += 1;
+        }
+        if delivered == self.pending.len() {
+            self.last_error = None;
+        }
+
+        Ok(self.pending.drain(..delivered).collect())
+    }
+}
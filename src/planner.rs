@@ -0,0 +1,335 @@
+//! Trip planning between two stations
+//!
+//! A [`TripPlanner`] looks for the trains of a [`TrainResponse`] that stop at
+//! an origin and later at a destination, and optionally for pairs of trains
+//! connecting at a common station. Every trip found is returned as an
+//! [`Itinerary`] made of one [`Leg`] per train, with its departure and
+//! arrival times (the estimates when known, otherwise the schedule) and the
+//! current delay of the train.
+//!
+//! By default trains that already departed from the station where the
+//! traveler would board them are left out.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{planner::TripPlanner, Client};
+//! use chrono::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let response = Client::new().trains().await?;
+//!     let planner = TripPlanner::new().min_connection(Duration::minutes(20));
+//!
+//!     for itinerary in planner.plan(&response, "WIL", "LNC") {
+//!         println!(
+//!             "{} -> {} ({} transfers)",
+//!             itinerary.departure(),
+//!             itinerary.arrival(),
+//!             itinerary.transfers()
+//!         );
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::Serialize;
+
+use crate::responses::{Train, TrainResponse, TrainStation, TrainStatus};
+
+/// The default minimum time between two trains of an itinerary
+pub const DEFAULT_MIN_CONNECTION_MINUTES: i64 = 15;
+
+/// A part of an itinerary travelled on a single train
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Leg {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// The code of the station where the traveler boards the train
+    pub from_code: String,
+
+    /// The name of the station where the traveler boards the train
+    pub from_name: String,
+
+    /// The code of the station where the traveler leaves the train
+    pub to_code: String,
+
+    /// The name of the station where the traveler leaves the train
+    pub to_name: String,
+
+    /// The scheduled departure of the train from the boarding station
+    pub scheduled_departure: DateTime<FixedOffset>,
+
+    /// The estimated departure of the train from the boarding station
+    pub estimated_departure: Option<DateTime<FixedOffset>>,
+
+    /// The scheduled arrival of the train at the alighting station
+    pub scheduled_arrival: DateTime<FixedOffset>,
+
+    /// The estimated arrival of the train at the alighting station
+    pub estimated_arrival: Option<DateTime<FixedOffset>>,
+
+    /// The current delay of the train in minutes (see [`Train::delay`]), a
+    /// negative value means the train is early.
+    pub delay_minutes: Option<i64>,
+}
+
+impl Leg {
+    fn new(train: &Train, from: &TrainStation, to: &TrainStation) -> Self {
+        Self {
+            train_id: train.train_id.clone(),
+            train_num: train.train_num,
+            route_name: train.route_name.clone(),
+            from_code: from.code.clone(),
+            from_name: from.name.clone(),
+            to_code: to.code.clone(),
+            to_name: to.name.clone(),
+            scheduled_departure: from.schedule_departure,
+            estimated_departure: from.departure,
+            scheduled_arrival: to.schedule_arrival,
+            estimated_arrival: to.arrival,
+            delay_minutes: train.delay().map(|delay| delay.num_minutes()),
+        }
+    }
+
+    /// Returns the best known departure time, the estimate if there is one
+    /// otherwise the schedule.
+    pub fn expected_departure(&self) -> DateTime<FixedOffset> {
+        self.estimated_departure.unwrap_or(self.scheduled_departure)
+    }
+
+    /// Returns the best known arrival time, the estimate if there is one
+    /// otherwise the schedule.
+    pub fn expected_arrival(&self) -> DateTime<FixedOffset> {
+        self.estimated_arrival.unwrap_or(self.scheduled_arrival)
+    }
+
+    /// Returns the expected time spent on the train
+    pub fn duration(&self) -> Duration {
+        self.expected_arrival() - self.expected_departure()
+    }
+}
+
+/// A trip from an origin to a destination made of one or more legs
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Itinerary {
+    legs: Vec<Leg>,
+}
+
+impl Itinerary {
+    /// Creates an itinerary from its legs in the order they are travelled,
+    /// or returns `None` if there is no leg
+    pub fn new(legs: Vec<Leg>) -> Option<Self> {
+        (!legs.is_empty()).then_some(Self { legs })
+    }
+
+    /// Returns the legs of the trip in the order they are travelled, there
+    /// is always at least one
+    pub fn legs(&self) -> &[Leg] {
+        &self.legs
+    }
+
+    /// Returns the expected departure from the origin
+    pub fn departure(&self) -> DateTime<FixedOffset> {
+        self.legs[0].expected_departure()
+    }
+
+    /// Returns the expected arrival at the destination
+    pub fn arrival(&self) -> DateTime<FixedOffset> {
+        self.legs[self.legs.len() - 1].expected_arrival()
+    }
+
+    /// Returns the expected duration of the whole trip, including the time
+    /// spent waiting for connections
+    pub fn duration(&self) -> Duration {
+        self.arrival() - self.departure()
+    }
+
+    /// Returns the number of times the traveler changes trains
+    pub fn transfers(&self) -> usize {
+        self.legs.len() - 1
+    }
+
+    /// Returns the expected time between arriving on a leg and departing on
+    /// the next one, for every transfer
+    pub fn connection_times(&self) -> Vec<Duration> {
+        self.legs
+            .windows(2)
+            .map(|legs| legs[1].expected_departure() - legs[0].expected_arrival())
+            .collect()
+    }
+}
+
+/// Finds itineraries between two stations
+#[derive(Debug, Clone)]
+pub struct TripPlanner {
+    min_connection: Duration,
+    include_departed: bool,
+}
+
+impl Default for TripPlanner {
+    fn default() -> Self {
+        Self {
+            min_connection: Duration::minutes(DEFAULT_MIN_CONNECTION_MINUTES),
+            include_departed: false,
+        }
+    }
+}
+
+impl TripPlanner {
+    /// Creates a planner with a minimum connection time of
+    /// [`DEFAULT_MIN_CONNECTION_MINUTES`] that ignores departed trains
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum time between arriving on a train and departing on
+    /// the next one
+    pub fn min_connection(mut self, min_connection: Duration) -> Self {
+        self.min_connection = min_connection;
+        self
+    }
+
+    /// Also considers trains that already departed from the station where
+    /// the traveler would board them
+    pub fn include_departed(mut self, include_departed: bool) -> Self {
+        self.include_departed = include_departed;
+        self
+    }
+
+    /// Returns the trains stopping at the origin and later at the
+    /// destination, ordered by their expected departure
+    pub fn direct(
+        &self,
+        response: &TrainResponse,
+        origin: &str,
+        destination: &str,
+    ) -> Vec<Itinerary> {
+        let mut itineraries: Vec<_> = response
+            .iter_trains()
+            .filter_map(|train| self.leg(train, origin, destination))
+            .map(|leg| Itinerary { legs: vec![leg] })
+            .collect();
+        itineraries.sort_by(|a, b| {
+            a.departure()
+                .cmp(&b.departure())
+                .then_with(|| a.legs[0].train_id.cmp(&b.legs[0].train_id))
+        });
+
+        itineraries
+    }
+
+    /// Returns the pairs of trains connecting at a common station, ordered by
+    /// their expected arrival
+    ///
+    /// Only trains that do not reach the destination themselves are used for
+    /// the first leg, and the first station along that train where the
+    /// connection leaves at least the minimum connection time is picked.
+    pub fn one_transfer(
+        &self,
+        response: &TrainResponse,
+        origin: &str,
+        destination: &str,
+    ) -> Vec<Itinerary> {
+        let mut itineraries = Vec::new();
+
+        for first in response.iter_trains() {
+            let Some(boarding) = self.boarding(first, origin) else {
+                continue;
+            };
+            if position(first, destination).is_some_and(|position| position > boarding) {
+                continue;
+            }
+
+            for second in response.iter_trains() {
+                if second.train_id == first.train_id {
+                    continue;
+                }
+
+                let connection = first.stations[boarding + 1..].iter().find_map(|transfer| {
+                    let inbound = Leg::new(first, &first.stations[boarding], transfer);
+                    let outbound = self.leg(second, &transfer.code, destination)?;
+
+                    (outbound.expected_departure() - inbound.expected_arrival()
+                        >= self.min_connection)
+                        .then(|| Itinerary {
+                            legs: vec![inbound, outbound],
+                        })
+                });
+                itineraries.extend(connection);
+            }
+        }
+
+        itineraries.sort_by(|a, b| {
+            a.arrival()
+                .cmp(&b.arrival())
+                .then_with(|| a.departure().cmp(&b.departure()))
+        });
+
+        itineraries
+    }
+
+    /// Returns the direct and one transfer itineraries together, ordered by
+    /// their expected arrival then by their number of transfers
+    pub fn plan(
+        &self,
+        response: &TrainResponse,
+        origin: &str,
+        destination: &str,
+    ) -> Vec<Itinerary> {
+        let mut itineraries = self.direct(response, origin, destination);
+        itineraries.extend(self.one_transfer(response, origin, destination));
+        itineraries.sort_by(|a, b| {
+            a.arrival()
+                .cmp(&b.arrival())
+                .then_with(|| a.transfers().cmp(&b.transfers()))
+        });
+
+        itineraries
+    }
+
+    /// Returns the position of the station where the traveler boards the
+    /// train, if the train can still be boarded there
+    fn boarding(&self, train: &Train, station_code: &str) -> Option<usize> {
+        position(train, station_code).filter(|&position| {
+            self.include_departed || train.stations[position].status != TrainStatus::Departed
+        })
+    }
+
+    fn leg(&self, train: &Train, origin: &str, destination: &str) -> Option<Leg> {
+        let from = self.boarding(train, origin)?;
+        let to = position(train, destination).filter(|&to| to > from)?;
+
+        Some(Leg::new(train, &train.stations[from], &train.stations[to]))
+    }
+}
+
+fn position(train: &Train, station_code: &str) -> Option<usize> {
+    train
+        .stations
+        .iter()
+        .position(|station| station.code.eq_ignore_ascii_case(station_code))
+}
+
+impl TrainResponse {
+    /// Returns the trains stopping at the origin and later at the
+    /// destination with the default [`TripPlanner`]
+    pub fn trips(&self, origin: &str, destination: &str) -> Vec<Itinerary> {
+        TripPlanner::new().direct(self, origin, destination)
+    }
+}
@@ -0,0 +1,360 @@
+//! Arrival time prediction
+//!
+//! The [`arrival`] of a station the train has not reached yet is the estimate
+//! published by the API, which often lags behind reality. An
+//! [`ArrivalPredictor`] computes its own estimate for every remaining station
+//! of a train and [`backtest`] measures how accurate a predictor would have
+//! been against recorded history.
+//!
+//! [`BaselinePredictor`] is a simple predictor combining the current speed of
+//! the train, the run and dwell times observed in the past and the current
+//! delay of the train.
+//!
+//! [`arrival`]: TrainStation::arrival
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::Serialize;
+
+use crate::{
+    export::StopEvent,
+    geo::Coordinate,
+    responses::{StationResponse, Train, TrainStation, TrainStatus},
+};
+
+#[cfg(feature = "sqlite")]
+use crate::storage::{self, Run, Store};
+
+/// Trains reporting a lower speed (in miles per hour) are considered stopped
+const MIN_MOVING_VELOCITY: f32 = 5.0;
+
+/// The predicted arrival of a train at one of its stations
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Prediction {
+    /// The code of the station
+    pub station_code: String,
+
+    /// The scheduled arrival at the station
+    pub scheduled_arrival: DateTime<FixedOffset>,
+
+    /// The arrival estimated by the API, if any
+    pub published_arrival: Option<DateTime<FixedOffset>>,
+
+    /// The arrival estimated by the predictor
+    pub predicted_arrival: DateTime<FixedOffset>,
+}
+
+impl Prediction {
+    fn new(station: &TrainStation, predicted_arrival: DateTime<FixedOffset>) -> Self {
+        Self {
+            station_code: station.code.clone(),
+            scheduled_arrival: station.schedule_arrival,
+            published_arrival: station.arrival,
+            predicted_arrival: predicted_arrival.with_timezone(station.schedule_arrival.offset()),
+        }
+    }
+}
+
+/// Predicts when a train will arrive at its remaining stations
+pub trait ArrivalPredictor {
+    /// Returns a prediction for every remaining station of the train (see
+    /// [`Train::remaining_stations`]) in the order the train stops at them.
+    fn predict(&self, train: &Train, now: DateTime<FixedOffset>) -> Vec<Prediction>;
+}
+
+/// Run times between stations and dwell times at stations observed in the
+/// past
+#[derive(Debug, Clone, Default)]
+pub struct RunTimes {
+    run_times: HashMap<(String, String), Vec<i64>>,
+    dwell_times: HashMap<String, Vec<i64>>,
+}
+
+impl RunTimes {
+    /// Creates an instance without any observation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the run and dwell times of a single run, the events must be
+    /// in the order the train stopped at the stations. Only the stations the
+    /// train departed from are taken into account.
+    pub fn add_run(&mut self, events: &[StopEvent]) {
+        let departed = |event: &&StopEvent| event.status == TrainStatus::Departed;
+
+        for event in events.iter().filter(departed) {
+            if let (Some(arrival), Some(departure)) = (event.actual_arrival, event.actual_departure)
+            {
+                self.dwell_times
+                    .entry(event.station_code.clone())
+                    .or_default()
+                    .push((departure - arrival).num_seconds());
+            }
+        }
+
+        for pair in events.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            if from.status != TrainStatus::Departed
+                || !matches!(to.status, TrainStatus::Station | TrainStatus::Departed)
+            {
+                continue;
+            }
+
+            if let (Some(departure), Some(arrival)) = (from.actual_departure, to.actual_arrival) {
+                self.run_times
+                    .entry((from.station_code.clone(), to.station_code.clone()))
+                    .or_default()
+                    .push((arrival - departure).num_seconds());
+            }
+        }
+    }
+
+    /// Records the run and dwell times of every stored run
+    #[cfg(feature = "sqlite")]
+    pub fn from_runs(runs: &[Run]) -> Self {
+        let mut run_times = Self::new();
+        for run in runs {
+            run_times.add_run(&run.stop_events);
+        }
+
+        run_times
+    }
+
+    /// Returns the median time it took to travel from one station to the
+    /// next one, if it was ever observed.
+    pub fn run_time(&self, from: &str, to: &str) -> Option<Duration> {
+        median(self.run_times.get(&(from.to_string(), to.to_string()))?)
+    }
+
+    /// Returns the median time trains stood at the station, if it was ever
+    /// observed.
+    pub fn dwell_time(&self, station_code: &str) -> Option<Duration> {
+        median(self.dwell_times.get(station_code)?)
+    }
+}
+
+fn median(seconds: &[i64]) -> Option<Duration> {
+    let mut seconds = seconds.to_vec();
+    seconds.sort_unstable();
+
+    seconds
+        .get(seconds.len() / 2)
+        .map(|seconds| Duration::seconds(*seconds))
+}
+
+/// A predictor using the current state of the train and past observations
+///
+/// The arrival at the next station is predicted, in order of preference:
+///
+/// 1. from the straight line distance to the station and the current speed
+///    of the train, if it is moving and the station coordinates are known
+/// 2. from the departure at the previous station and the observed run time
+///    between both stations
+/// 3. by applying the current delay of the train to the schedule
+///
+/// The following stations are predicted by adding the observed (or else
+/// scheduled) dwell and run times, assuming the train never departs a station
+/// before its scheduled departure.
+#[derive(Debug, Clone, Default)]
+pub struct BaselinePredictor {
+    run_times: RunTimes,
+    stations: HashMap<String, Coordinate>,
+}
+
+impl BaselinePredictor {
+    /// Creates a predictor using the provided observations
+    pub fn new(run_times: RunTimes) -> Self {
+        Self {
+            run_times,
+            stations: HashMap::new(),
+        }
+    }
+
+    /// Uses the coordinates of the stations to predict when a moving train
+    /// will reach its next station
+    pub fn with_stations(mut self, stations: &StationResponse) -> Self {
+        self.stations = stations
+            .0
+            .values()
+            .map(|station| (station.code.clone(), station.coordinate()))
+            .collect();
+        self
+    }
+
+    fn next_arrival(
+        &self,
+        train: &Train,
+        next: &TrainStation,
+        now: DateTime<FixedOffset>,
+    ) -> DateTime<FixedOffset> {
+        if train.velocity >= MIN_MOVING_VELOCITY {
+            if let Some(coordinate) = self.stations.get(&next.code) {
+                let hours = train.coordinate().distance_to(*coordinate) / train.velocity as f64;
+                let travel = Duration::milliseconds((hours * 3_600_000.0) as i64);
+
+                return (train.last_value + travel).max(now);
+            }
+        }
+
+        let previous = train
+            .stations
+            .iter()
+            .rev()
+            .find(|station| station.status == TrainStatus::Departed);
+        if let Some(previous) = previous {
+            let run_time = self.run_times.run_time(&previous.code, &next.code);
+            if let (Some(departure), Some(run_time)) = (previous.departure, run_time) {
+                return (departure + run_time).max(now);
+            }
+        }
+
+        (next.schedule_arrival + train.delay().unwrap_or_else(Duration::zero)).max(now)
+    }
+
+    fn departure(
+        &self,
+        station: &TrainStation,
+        arrival: DateTime<FixedOffset>,
+    ) -> DateTime<FixedOffset> {
+        let dwell = self
+            .run_times
+            .dwell_time(&station.code)
+            .unwrap_or(station.schedule_departure - station.schedule_arrival);
+
+        (arrival + dwell).max(station.schedule_departure)
+    }
+}
+
+impl ArrivalPredictor for BaselinePredictor {
+    fn predict(&self, train: &Train, now: DateTime<FixedOffset>) -> Vec<Prediction> {
+        let remaining: Vec<_> = train.remaining_stations().collect();
+        let Some(next) = remaining.first() else {
+            return Vec::new();
+        };
+
+        let mut arrival = match next.status {
+            TrainStatus::Station => next.arrival.unwrap_or(now),
+            _ => self.next_arrival(train, next, now),
+        };
+        let mut predictions = vec![Prediction::new(next, arrival)];
+
+        for pair in remaining.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            let run_time = self
+                .run_times
+                .run_time(&from.code, &to.code)
+                .unwrap_or(to.schedule_arrival - from.schedule_departure);
+
+            arrival = self.departure(from, arrival) + run_time;
+            predictions.push(Prediction::new(to, arrival));
+        }
+
+        predictions
+    }
+}
+
+/// The accuracy of a predictor over past runs
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct Backtest {
+    /// Number of predictions that could be compared to an actual arrival
+    pub samples: usize,
+
+    /// The mean absolute error of the predictions in minutes
+    pub mean_absolute_error_minutes: f64,
+
+    /// Number of published estimates that could be compared to an actual
+    /// arrival
+    pub published_samples: usize,
+
+    /// The mean absolute error of the estimates published by the API in
+    /// minutes, for comparison
+    pub published_mean_absolute_error_minutes: f64,
+}
+
+/// Replays past snapshots of trains through the predictor and compares the
+/// predictions to the actual arrivals.
+///
+/// Each case is a snapshot of a train, predicted as of its [`updated_at`],
+/// together with the stop events recorded once the run was over. Stations
+/// the train was already standing at in the snapshot are not counted.
+///
+/// [`updated_at`]: Train::updated_at
+pub fn backtest<'a, P, I>(predictor: &P, cases: I) -> Backtest
+where
+    P: ArrivalPredictor + ?Sized,
+    I: IntoIterator<Item = (&'a Train, &'a [StopEvent])>,
+{
+    let mut errors = Vec::new();
+    let mut published_errors = Vec::new();
+
+    for (snapshot, outcome) in cases {
+        let enroute: Vec<_> = snapshot
+            .stations
+            .iter()
+            .filter(|station| station.status == TrainStatus::Enroute)
+            .map(|station| station.code.as_str())
+            .collect();
+
+        for prediction in predictor.predict(snapshot, snapshot.updated_at) {
+            if !enroute.contains(&prediction.station_code.as_str()) {
+                continue;
+            }
+
+            let actual = outcome
+                .iter()
+                .filter(|event| {
+                    matches!(event.status, TrainStatus::Station | TrainStatus::Departed)
+                })
+                .find(|event| event.station_code == prediction.station_code)
+                .and_then(|event| event.actual_arrival);
+            let Some(actual) = actual else {
+                continue;
+            };
+
+            let error = |predicted: DateTime<FixedOffset>| {
+                (predicted.to_utc() - actual).num_seconds().abs() as f64 / 60.0
+            };
+            errors.push(error(prediction.predicted_arrival));
+            if let Some(published) = prediction.published_arrival {
+                published_errors.push(error(published));
+            }
+        }
+    }
+
+    let mean = |errors: &[f64]| {
+        if errors.is_empty() {
+            0.0
+        } else {
+            errors.iter().sum::<f64>() / errors.len() as f64
+        }
+    };
+
+    Backtest {
+        samples: errors.len(),
+        mean_absolute_error_minutes: mean(&errors),
+        published_samples: published_errors.len(),
+        published_mean_absolute_error_minutes: mean(&published_errors),
+    }
+}
+
+/// Backtests the predictor against every snapshot of the stored runs
+#[cfg(feature = "sqlite")]
+pub fn backtest_runs<P>(predictor: &P, store: &Store, runs: &[Run]) -> storage::Result<Backtest>
+where
+    P: ArrivalPredictor + ?Sized,
+{
+    let snapshots = runs
+        .iter()
+        .map(|run| Ok((store.snapshots(run)?, run)))
+        .collect::<storage::Result<Vec<_>>>()?;
+
+    Ok(backtest(
+        predictor,
+        snapshots.iter().flat_map(|(snapshots, run)| {
+            snapshots
+                .iter()
+                .map(|snapshot| (snapshot, run.stop_events.as_slice()))
+        }),
+    ))
+}
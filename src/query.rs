@@ -0,0 +1,147 @@
+//! Composable train queries
+//!
+//! A [`TrainQuery`] is built by chaining the criteria a train must satisfy.
+//! Every criterion is compiled into a predicate over [`Train`] and a train
+//! matches the query when it satisfies all of them. Running a query over a
+//! [`TrainResponse`] borrows the matching trains without cloning them.
+//!
+//! Route names and station codes are compared case-insensitively.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{query::TrainQuery, responses::TrainState, Client};
+//! use chrono::Duration;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let response = Client::new().trains().await?;
+//!     let query = TrainQuery::new()
+//!         .route_name("Keystone")
+//!         .state(TrainState::Active)
+//!         .delayed_more_than(Duration::minutes(10));
+//!
+//!     for train in query.run(&response) {
+//!         println!("{} is running late", train.train_id);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use chrono::Duration;
+
+use crate::{
+    geo::Region,
+    responses::{Heading, Train, TrainResponse, TrainState},
+};
+
+type Predicate<'a> = Box<dyn Fn(&Train) -> bool + 'a>;
+
+/// A set of criteria that trains must satisfy
+///
+/// An empty query matches every train.
+#[derive(Default)]
+pub struct TrainQuery<'a> {
+    predicates: Vec<Predicate<'a>>,
+}
+
+impl<'a> TrainQuery<'a> {
+    /// Creates a query matching every train
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches trains satisfying the provided predicate
+    pub fn matching<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Train) -> bool + 'a,
+    {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Only matches trains of the provided route (see [`route_name`])
+    ///
+    /// [`route_name`]: Train::route_name
+    pub fn route_name<S: Into<String>>(self, route_name: S) -> Self {
+        let route_name = route_name.into();
+        self.matching(move |train| train.route_name.eq_ignore_ascii_case(&route_name))
+    }
+
+    /// Only matches trains with the provided [`train_num`]
+    ///
+    /// [`train_num`]: Train::train_num
+    pub fn train_num(self, train_num: u32) -> Self {
+        self.matching(move |train| train.train_num == train_num)
+    }
+
+    /// Only matches trains departing from the provided station (see
+    /// [`origin_code`])
+    ///
+    /// [`origin_code`]: Train::origin_code
+    pub fn origin<S: Into<String>>(self, station_code: S) -> Self {
+        let station_code = station_code.into();
+        self.matching(move |train| train.origin_code.eq_ignore_ascii_case(&station_code))
+    }
+
+    /// Only matches trains terminating at the provided station (see
+    /// [`destination_code`])
+    ///
+    /// [`destination_code`]: Train::destination_code
+    pub fn destination<S: Into<String>>(self, station_code: S) -> Self {
+        let station_code = station_code.into();
+        self.matching(move |train| train.destination_code.eq_ignore_ascii_case(&station_code))
+    }
+
+    /// Only matches trains in the provided [`TrainState`]
+    pub fn state(self, state: TrainState) -> Self {
+        self.matching(move |train| train.train_state == state)
+    }
+
+    /// Only matches trains stopping at the provided station, whether they
+    /// already departed from it or not
+    pub fn stops_at<S: Into<String>>(self, station_code: S) -> Self {
+        let station_code = station_code.into();
+        self.matching(move |train| {
+            train
+                .stations
+                .iter()
+                .any(|station| station.code.eq_ignore_ascii_case(&station_code))
+        })
+    }
+
+    /// Only matches trains running more than `delay` late, trains with an
+    /// unknown delay never match (see [`Train::delay`])
+    pub fn delayed_more_than(self, delay: Duration) -> Self {
+        self.matching(move |train| train.delay().is_some_and(|current| current > delay))
+    }
+
+    /// Only matches trains with the provided [`Heading`]
+    pub fn heading(self, heading: Heading) -> Self {
+        self.matching(move |train| train.heading == heading)
+    }
+
+    /// Only matches trains whose last reported position lies inside the
+    /// region
+    pub fn within<R: Region + 'a>(self, region: R) -> Self {
+        self.matching(move |train| region.contains(train.coordinate()))
+    }
+
+    /// Returns `true` if the train satisfies every criterion of the query
+    pub fn matches(&self, train: &Train) -> bool {
+        self.predicates.iter().all(|predicate| predicate(train))
+    }
+
+    /// Compiles the query into a single predicate
+    pub fn into_predicate(self) -> impl Fn(&Train) -> bool + 'a {
+        move |train| self.matches(train)
+    }
+
+    /// Returns the trains of the response matching the query
+    pub fn run<'r>(&'r self, response: &'r TrainResponse) -> impl Iterator<Item = &'r Train> + 'r {
+        response
+            .iter_trains()
+            .filter(move |train| self.matches(train))
+    }
+}
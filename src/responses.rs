@@ -1,10 +1,13 @@
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{hash_map, BTreeMap, HashMap},
+    fmt, iter,
+};
 
-use chrono::{DateTime, FixedOffset};
-use serde::{de, Deserialize};
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::{de, Deserialize, Serialize};
 
 /// The response from the `/trains` or `/trains/{:train_id}` endpoint.
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Clone)]
 pub struct TrainResponse(
     /// Each key in the hashmap is the string representation of the
     /// [`train_num`] field. The value is a list of trains that have the
@@ -62,8 +65,104 @@ impl<'de> Deserialize<'de> for TrainResponse {
     }
 }
 
+impl TrainResponse {
+    /// Returns every train of the response regardless of its train number
+    pub fn iter_trains(&self) -> impl Iterator<Item = &Train> {
+        self.0.values().flatten()
+    }
+
+    /// Returns the train with the provided [`train_id`]
+    ///
+    /// This walks every train, use [`TrainResponse::index`] to look up many
+    /// trains.
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn get_by_id(&self, train_id: &str) -> Option<&Train> {
+        self.iter_trains().find(|train| train.train_id == train_id)
+    }
+
+    /// Groups the trains by [`route_name`], each group is sorted by
+    /// [`train_id`]
+    ///
+    /// The keys are the route names exactly as provided by the API, use
+    /// [`index`] to look a route up ignoring case.
+    ///
+    /// [`route_name`]: Train::route_name
+    /// [`index`]: Self::index
+    /// [`train_id`]: Train::train_id
+    pub fn by_route(&self) -> BTreeMap<&str, Vec<&Train>> {
+        group(
+            self.iter_trains()
+                .map(|train| (train.route_name.as_str(), train)),
+        )
+    }
+
+    /// Groups the trains by the [`code`] of every station they stop at, each
+    /// group is sorted by [`train_id`]
+    ///
+    /// The keys are the station codes exactly as provided by the API, use
+    /// [`index`] to look a station up ignoring case.
+    ///
+    /// [`code`]: TrainStation::code
+    /// [`index`]: Self::index
+    /// [`train_id`]: Train::train_id
+    pub fn by_station(&self) -> BTreeMap<&str, Vec<&Train>> {
+        group(self.iter_trains().flat_map(|train| {
+            train
+                .stations
+                .iter()
+                .map(move |station| (station.code.as_str(), train))
+        }))
+    }
+
+    /// Returns the number of trains in the response
+    pub fn len(&self) -> usize {
+        self.0.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the response does not contain any train
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn group<'a, K: Ord, I>(entries: I) -> BTreeMap<K, Vec<&'a Train>>
+where
+    I: Iterator<Item = (K, &'a Train)>,
+{
+    let mut groups: BTreeMap<K, Vec<&Train>> = BTreeMap::new();
+    for (key, train) in entries {
+        groups.entry(key).or_default().push(train);
+    }
+
+    for trains in groups.values_mut() {
+        trains.sort_by(|a, b| a.train_id.cmp(&b.train_id));
+        trains.dedup_by(|a, b| a.train_id == b.train_id);
+    }
+
+    groups
+}
+
+impl IntoIterator for TrainResponse {
+    type Item = Train;
+    type IntoIter = iter::Flatten<hash_map::IntoValues<String, Vec<Train>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_values().flatten()
+    }
+}
+
+impl<'a> IntoIterator for &'a TrainResponse {
+    type Item = &'a Train;
+    type IntoIter = iter::Flatten<hash_map::Values<'a, String, Vec<Train>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.values().flatten()
+    }
+}
+
 /// Represents an Amtrak train
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Train {
     /// The human readable route name of this train.
     ///
@@ -220,7 +319,30 @@ pub struct Train {
     pub object_id: u32,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl Train {
+    /// Returns the stations that this train has not yet departed from, in the
+    /// order the train will stop at each.
+    ///
+    /// This includes the station the train is currently stopped at (if any).
+    pub fn remaining_stations(&self) -> impl Iterator<Item = &TrainStation> {
+        self.stations
+            .iter()
+            .filter(|station| station.status != TrainStatus::Departed)
+    }
+
+    /// Returns how late the train is running based on the [`train_timely`]
+    /// field. A negative duration means the train is running early.
+    ///
+    /// Returns `None` when the API does not know the delay of the train (for
+    /// example `Unknown` or `NaN Minutes Early`).
+    ///
+    /// [`train_timely`]: Self::train_timely
+    pub fn delay(&self) -> Option<Duration> {
+        parse_timely(&self.train_timely)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TrainStation {
     /// The full human readable name of the station.
     ///
@@ -330,8 +452,61 @@ pub struct TrainStation {
     pub status: TrainStatus,
 }
 
+impl TrainStation {
+    /// Returns how late the train arrived (or is predicted to arrive) at this
+    /// station based on the [`arrival_comment`] field. A negative duration
+    /// means the train is early.
+    ///
+    /// [`arrival_comment`]: Self::arrival_comment
+    pub fn arrival_delay(&self) -> Option<Duration> {
+        parse_timely(&self.arrival_comment)
+    }
+
+    /// Returns how late the train departed (or is predicted to depart) from
+    /// this station based on the [`departure_comment`] field. A negative
+    /// duration means the train is early.
+    ///
+    /// [`departure_comment`]: Self::departure_comment
+    pub fn departure_delay(&self) -> Option<Duration> {
+        parse_timely(&self.departure_comment)
+    }
+}
+
+/// Parses the human readable timelyness strings returned by the API (such as
+/// `19 Minutes Late` or `1 Hours, 5 Minutes Early`) into a signed duration
+/// where positive values represent a late train.
+fn parse_timely(timely: &str) -> Option<Duration> {
+    let timely = timely.trim();
+
+    if timely.eq_ignore_ascii_case("on time") {
+        return Some(Duration::zero());
+    }
+
+    let (amount, sign) = if let Some(amount) = timely.strip_suffix("Late") {
+        (amount, 1)
+    } else if let Some(amount) = timely.strip_suffix("Early") {
+        (amount, -1)
+    } else {
+        return None;
+    };
+
+    let mut minutes = 0;
+    for part in amount.split(',') {
+        let mut words = part.split_whitespace();
+        let value = words.next()?.parse::<i64>().ok()?;
+
+        minutes += match words.next()? {
+            "Hour" | "Hours" => value * 60,
+            "Minute" | "Minutes" => value,
+            _ => return None,
+        };
+    }
+
+    Some(Duration::minutes(sign * minutes))
+}
+
 /// Describes a train's heading using cardinal directions
-#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 pub enum Heading {
     /// North heading
     N,
@@ -367,7 +542,7 @@ pub enum Heading {
 /// [`Station`]: Station
 /// [`Train`]: Train
 /// [`stations`]: Train::stations
-#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 pub enum TrainStatus {
     /// The train has not yet arrived at the specified station.
     Enroute,
@@ -382,7 +557,7 @@ pub enum TrainStatus {
     Unknown,
 }
 
-#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 pub enum TrainState {
     /// The train is awaiting departure from its origin station
     Predeparture,
@@ -395,7 +570,7 @@ pub enum TrainState {
 }
 
 /// The response from the `/stations` or `/stations/{:station_code}` endpoint.
-#[derive(Debug, Clone)]
+#[derive(Debug, Serialize, Clone)]
 pub struct StationResponse(
     /// Each key in the hashmap is the unique station code which will match the
     /// [`code`] field. The value is the [`Station`] structure that is
@@ -451,7 +626,7 @@ impl<'de> Deserialize<'de> for StationResponse {
 }
 
 /// Represents a unique station that Amtrak services
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Station {
     /// The full human readable name of the station.
     ///
@@ -0,0 +1,309 @@
+//! Fuzzy station search
+//!
+//! Users type "philly", "30th street" or "penn station" rather than `PHL`.
+//! [`StationSearch`] ranks the stations of a [`StationResponse`] by how well
+//! their code, name and city match such a query and returns scored matches,
+//! the best one first.
+//!
+//! Matching is forgiving in several ways:
+//!
+//! * every word of the query may match the start of a word (`phil`) or
+//!   contain a small typo (`philadelpia`)
+//! * common abbreviations are expanded (`st` matches `Street` and `Saint`)
+//! * aliases map nicknames to station codes (`philly` is `PHL`)
+//! * ambiguous aliases list several stations in order of preference, this is
+//!   how the multiple "Penn" stations are told apart: `penn station` ranks
+//!   New York first while `penn station baltimore` ranks Baltimore first.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::Client;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let stations = Client::new().stations().await?;
+//!
+//!     for found in stations.search("philly") {
+//!         println!("{} ({:.2})", found.station.name, found.score);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use std::cmp::Ordering;
+
+use crate::responses::{Station, StationResponse};
+
+/// Matches scoring lower are left out of the results
+const MIN_SCORE: f64 = 0.6;
+
+/// Built-in aliases, each listing its stations in order of preference
+const ALIASES: &[(&str, &[&str])] = &[
+    ("penn station", &["NYP", "NWK", "BAL"]),
+    ("penn", &["NYP", "NWK", "BAL"]),
+    ("philly", &["PHL"]),
+    ("30th street", &["PHL"]),
+    ("nyc", &["NYP"]),
+    ("moynihan", &["NYP"]),
+    ("dc", &["WAS"]),
+    ("union station", &["WAS", "CHI", "LAX", "TWO"]),
+];
+
+/// Built-in abbreviations and the words they stand for
+const ABBREVIATIONS: &[(&str, &[&str])] = &[
+    ("st", &["street", "saint"]),
+    ("ste", &["sainte"]),
+    ("ave", &["avenue"]),
+    ("ctr", &["center", "centre"]),
+    ("jct", &["junction"]),
+    ("sq", &["square"]),
+    ("intl", &["international"]),
+    ("arpt", &["airport"]),
+    ("ft", &["fort"]),
+    ("mt", &["mount"]),
+    ("n", &["north"]),
+    ("s", &["south"]),
+    ("e", &["east"]),
+    ("w", &["west"]),
+];
+
+/// A station matching a search query
+#[derive(Debug, Clone)]
+pub struct StationMatch<'a> {
+    /// The matching station
+    pub station: &'a Station,
+
+    /// How well the station matches the query, between `0.0` and `1.0`
+    pub score: f64,
+}
+
+/// A configurable station search
+#[derive(Debug, Clone)]
+pub struct StationSearch {
+    aliases: Vec<(Vec<String>, Vec<String>)>,
+    abbreviations: Vec<(String, Vec<String>)>,
+}
+
+impl Default for StationSearch {
+    fn default() -> Self {
+        let mut search = Self {
+            aliases: Vec::new(),
+            abbreviations: Vec::new(),
+        };
+
+        for (alias, codes) in ALIASES {
+            search = search.with_alias(alias, codes);
+        }
+        for (abbreviation, words) in ABBREVIATIONS {
+            search = search.with_abbreviation(abbreviation, words);
+        }
+
+        search
+    }
+}
+
+impl StationSearch {
+    /// Creates a search with the built-in aliases and abbreviations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps an alias to one or several station codes, in order of
+    /// preference
+    ///
+    /// Aliases added later take precedence over the built-in ones.
+    pub fn with_alias<S: AsRef<str>>(mut self, alias: &str, codes: &[S]) -> Self {
+        self.aliases.insert(
+            0,
+            (
+                words(alias),
+                codes
+                    .iter()
+                    .map(|code| code.as_ref().to_uppercase())
+                    .collect(),
+            ),
+        );
+        self
+    }
+
+    /// Lets a word of the query stand for any of the provided words
+    pub fn with_abbreviation<S: AsRef<str>>(mut self, abbreviation: &str, words: &[S]) -> Self {
+        self.abbreviations.push((
+            abbreviation.to_lowercase(),
+            words
+                .iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        ));
+        self
+    }
+
+    /// Returns the stations matching the query, the best match first
+    pub fn search<'a>(&self, stations: &'a StationResponse, query: &str) -> Vec<StationMatch<'a>> {
+        let query = words(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<_> = stations
+            .0
+            .values()
+            .filter_map(|station| {
+                let (score, preference) = self.score(station, &query);
+                (score >= MIN_SCORE).then_some((StationMatch { station, score }, preference))
+            })
+            .collect();
+
+        matches.sort_by(|(a, a_preference), (b, b_preference)| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(a_preference.cmp(b_preference))
+                .then_with(|| a.station.name.cmp(&b.station.name))
+        });
+
+        matches.into_iter().map(|(found, _)| found).collect()
+    }
+
+    /// Returns the score of the station and its preference among the
+    /// stations of the matching aliases (lower is better)
+    fn score(&self, station: &Station, query: &[String]) -> (f64, usize) {
+        if query.len() == 1 && query[0].eq_ignore_ascii_case(&station.code) {
+            return (1.0, 0);
+        }
+
+        let mut score = self.text_score(station, query);
+        let mut preference = usize::MAX;
+
+        for (alias, codes) in &self.aliases {
+            let Some(rank) = codes.iter().position(|code| *code == station.code) else {
+                continue;
+            };
+            let Some(rest) = remove_phrase(query, alias) else {
+                continue;
+            };
+
+            // The words outside of the alias must describe the station too
+            let alias_score = if rest.is_empty() {
+                0.9
+            } else {
+                match self.text_score(station, &rest) {
+                    rest_score if rest_score >= MIN_SCORE => 0.95 + 0.05 * rest_score,
+                    _ => 0.45,
+                }
+            };
+
+            score = score.max(alias_score);
+            preference = preference.min(rank);
+        }
+
+        (score, preference)
+    }
+
+    /// Returns the average similarity of the query words to the words of the
+    /// station name and city
+    fn text_score(&self, station: &Station, query: &[String]) -> f64 {
+        let name = words(&station.name);
+        let city = words(&station.city);
+
+        let total: f64 = query
+            .iter()
+            .map(|word| {
+                let alternatives = self.alternatives(word);
+                let best = |candidates: &[String]| {
+                    alternatives
+                        .iter()
+                        .flat_map(|alternative| {
+                            candidates
+                                .iter()
+                                .map(move |candidate| similarity(alternative, candidate))
+                        })
+                        .fold(0.0, f64::max)
+                };
+
+                best(&name).max(0.9 * best(&city))
+            })
+            .sum();
+
+        total / query.len() as f64
+    }
+
+    /// Returns the word itself followed by what it may abbreviate
+    fn alternatives<'a>(&'a self, word: &'a str) -> Vec<&'a str> {
+        let mut alternatives = vec![word];
+        for (abbreviation, words) in &self.abbreviations {
+            if abbreviation == word {
+                alternatives.extend(words.iter().map(String::as_str));
+            }
+        }
+
+        alternatives
+    }
+}
+
+/// Returns the query without the first occurrence of the phrase, or `None`
+/// if the query does not contain it
+fn remove_phrase(query: &[String], phrase: &[String]) -> Option<Vec<String>> {
+    if phrase.is_empty() || phrase.len() > query.len() {
+        return None;
+    }
+
+    let start = query
+        .windows(phrase.len())
+        .position(|window| window == phrase)?;
+    let mut rest = query[..start].to_vec();
+    rest.extend_from_slice(&query[start + phrase.len()..]);
+
+    Some(rest)
+}
+
+/// Returns how similar a query word is to a word of the station
+fn similarity(word: &str, candidate: &str) -> f64 {
+    if word == candidate {
+        return 1.0;
+    }
+    if word.len() >= 2 && candidate.starts_with(word) {
+        return 0.85;
+    }
+
+    let length = word.chars().count().max(candidate.chars().count());
+    let similarity = 1.0 - levenshtein(word, candidate) as f64 / length as f64;
+    if length >= 4 && similarity >= 0.75 {
+        similarity * 0.9
+    } else {
+        0.0
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Splits the text into lowercase alphanumeric words
+pub(crate) fn words(text: &str) -> Vec<String> {
+    text.split(|character: char| !character.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl StationResponse {
+    /// Searches the stations with the default [`StationSearch`]
+    pub fn search(&self, query: &str) -> Vec<StationMatch<'_>> {
+        StationSearch::new().search(self, query)
+    }
+}
@@ -0,0 +1,540 @@
+//! Embedded HTTP server
+//!
+//! The [`Server`] periodically polls the Amtrak API through a [`Client`],
+//! caches the latest responses and re-exposes them as REST endpoints with a
+//! consistent JSON shape:
+//!
+//! * `GET /trains` - Every train as a JSON array sorted by [`train_id`]
+//! * `GET /trains/{id}` - The train with the provided [`train_id`]
+//! * `GET /stations/{code}/board` - The departure [`Board`] of a station
+//! * `GET /geojson` - Every train as a GeoJSON `FeatureCollection`
+//! * `GET /events` - A Server-Sent Events stream of [`TrainEvent`]s
+//! * `GET /ws` - A WebSocket stream of [`TrainEvent`]s
+//! * `GET /metrics` - The [`NetworkMetrics`] of the cached trains and the
+//!   [`ClientMetrics`] of the poller in the Prometheus text format
+//!
+//! Both event streams accept the `train_ids`, `routes` and `stations` query
+//! parameters, each a comma separated list, to only receive the events
+//! matching an [`EventFilter`]. WebSocket clients can also replace their
+//! filter at any time by sending it as a JSON text message using the same
+//! names (for example `{"routes": ["Keystone"], "stations": ["PHL"]}`).
+//!
+//! Unlike the upstream API, every JSON key is snake_case (trains are returned
+//! with `train_id` and `last_value` rather than `trainID` and `lastValTS`),
+//! except for `/geojson` which keeps the camelCase feature properties of
+//! [`Train::to_geojson`] so it can be read back by
+//! [`RouteShapes::from_geojson`]. An empty list of trains is always returned as an empty array and errors
+//! are always returned as an object with an `error` field alongside the
+//! matching HTTP status code.
+//!
+//! [`train_id`]: crate::responses::Train::train_id
+//! [`Train::to_geojson`]: crate::responses::Train::to_geojson
+//! [`RouteShapes::from_geojson`]: crate::shape::RouteShapes::from_geojson
+//! [`Board`]: crate::board::Board
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+
+use crate::{
+    errors,
+    events::{self, EventFilter, TrainEvent, TrainEventKind},
+    metrics::ClientMetrics,
+    responses::{
+        Heading, StationResponse, Train, TrainResponse, TrainState, TrainStation, TrainStatus,
+    },
+    Client,
+};
+
+pub type Result<T> = std::result::Result<T, errors::Error>;
+
+/// Default amount of time between two polls of the Amtrak API
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Number of events buffered for subscribers that are falling behind
+const EVENT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    trains: Option<Arc<TrainResponse>>,
+    stations: Option<Arc<StationResponse>>,
+}
+
+/// The latest responses received from the Amtrak API
+///
+/// The cache is cheap to clone and every clone shares the same data.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    snapshot: Arc<RwLock<Snapshot>>,
+    events: broadcast::Sender<TrainEvent>,
+    metrics: Arc<ClientMetrics>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            snapshot: Arc::default(),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            metrics: Arc::default(),
+        }
+    }
+}
+
+impl Cache {
+    /// Creates a new empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached trains, if they have been fetched yet
+    pub fn trains(&self) -> Option<Arc<TrainResponse>> {
+        self.snapshot.read().unwrap().trains.clone()
+    }
+
+    /// Returns the cached stations, if they have been fetched yet
+    pub fn stations(&self) -> Option<Arc<StationResponse>> {
+        self.snapshot.read().unwrap().stations.clone()
+    }
+
+    /// Replaces the cached trains and publishes the [`TrainEvent`]s that
+    /// happened since the previously cached trains to every subscriber.
+    pub fn set_trains(&self, trains: TrainResponse) {
+        let trains = Arc::new(trains);
+        let previous = self
+            .snapshot
+            .write()
+            .unwrap()
+            .trains
+            .replace(trains.clone());
+
+        if let Some(previous) = previous {
+            for event in events::diff(&previous, &trains) {
+                // Sending only fails when nobody is subscribed
+                let _ = self.events.send(event);
+            }
+        }
+    }
+
+    /// Replaces the cached stations
+    pub fn set_stations(&self, stations: StationResponse) {
+        self.snapshot.write().unwrap().stations = Some(Arc::new(stations));
+    }
+
+    /// Returns the metrics the cache hits and misses are recorded into
+    pub fn metrics(&self) -> &Arc<ClientMetrics> {
+        &self.metrics
+    }
+
+    /// Returns a receiver of every [`TrainEvent`] published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<TrainEvent> {
+        self.events.subscribe()
+    }
+}
+
+/// A server re-exposing the data of the Amtrak API
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use amtrak_api::{server::Server, Client};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+///
+///     Server::new(Client::new())
+///         .with_base_path("/amtrak")
+///         .serve(listener)
+///         .await?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Server {
+    client: Client,
+    cache: Cache,
+    interval: Duration,
+    base_path: String,
+}
+
+impl Server {
+    /// Creates a new server polling the API using the provided client
+    pub fn new(client: Client) -> Self {
+        Self {
+            cache: Cache {
+                metrics: client.metrics().clone(),
+                ..Cache::new()
+            },
+            client,
+            interval: DEFAULT_POLL_INTERVAL,
+            base_path: String::new(),
+        }
+    }
+
+    /// Sets the amount of time between two polls of the Amtrak API
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Serves every endpoint under the provided path (for example `/amtrak`)
+    /// instead of the root, useful when running behind a reverse proxy.
+    pub fn with_base_path(mut self, base_path: &str) -> Self {
+        self.base_path = base_path.trim_end_matches('/').to_string();
+        if !self.base_path.is_empty() && !self.base_path.starts_with('/') {
+            self.base_path.insert(0, '/');
+        }
+        self
+    }
+
+    /// Returns the cache the endpoints are served from
+    pub fn cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    /// Fetches the trains and stations from the API and stores them in the
+    /// cache. The previously cached data is kept if a request fails.
+    pub async fn refresh(&self) -> Result<()> {
+        let trains = self.client.trains().await?;
+        self.cache.set_trains(trains);
+
+        let stations = self.client.stations().await?;
+        self.cache.set_stations(stations);
+
+        Ok(())
+    }
+
+    /// Returns the router serving the endpoints from the cache, nested under
+    /// the base path.
+    pub fn router(&self) -> Router {
+        let router = router(self.cache.clone());
+
+        if self.base_path.is_empty() {
+            router
+        } else {
+            Router::new().nest(&self.base_path, router)
+        }
+    }
+
+    /// Polls the API in the background and serves the endpoints on the
+    /// provided listener until an IO error occurs.
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        let router = self.router();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+                // Failed polls are retried on the next tick, in the meantime
+                // the endpoints keep serving the previous data
+                let _ = self.refresh().await;
+            }
+        });
+
+        axum::serve(listener, router).await
+    }
+}
+
+/// Returns a router serving the endpoints from the provided cache
+pub fn router(cache: Cache) -> Router {
+    Router::new()
+        .route("/trains", get(get_trains))
+        .route("/trains/{id}", get(get_train))
+        .route("/stations/{code}/board", get(get_board))
+        .route("/geojson", get(get_geojson))
+        .route("/events", get(get_events))
+        .route("/ws", get(get_ws))
+        .route("/metrics", get(get_metrics))
+        .with_state(cache)
+}
+
+/// An error returned to the caller as `{"error": "..."}`
+#[derive(Debug)]
+struct ApiError(StatusCode, &'static str);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult = std::result::Result<Response, ApiError>;
+
+fn cached_trains(cache: &Cache) -> std::result::Result<Arc<TrainResponse>, ApiError> {
+    match cache.trains() {
+        Some(trains) => {
+            cache.metrics.record_cache_hit();
+            Ok(trains)
+        }
+        None => {
+            cache.metrics.record_cache_miss();
+            Err(ApiError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The trains have not been fetched yet",
+            ))
+        }
+    }
+}
+
+/// A train as returned by the `/trains` endpoints, with the snake_case keys
+/// used by every endpoint instead of the keys of the upstream API
+#[derive(Debug, Serialize)]
+struct TrainBody<'a> {
+    train_id: &'a str,
+    train_num: u32,
+    route_name: &'a str,
+    lat: f64,
+    lon: f64,
+    heading: Heading,
+    velocity: f32,
+    train_timely: &'a str,
+    train_state: TrainState,
+    status_message: &'a str,
+    event_code: &'a str,
+    event_tz: Option<&'a str>,
+    event_name: Option<&'a str>,
+    origin_code: &'a str,
+    origin_tz: &'a str,
+    origin_name: &'a str,
+    destination_code: &'a str,
+    destination_tz: &'a str,
+    destination_name: &'a str,
+    created_at: DateTime<FixedOffset>,
+    updated_at: DateTime<FixedOffset>,
+    last_value: DateTime<FixedOffset>,
+    object_id: u32,
+    stations: Vec<TrainStationBody<'a>>,
+}
+
+/// A stop of a [`TrainBody`]
+#[derive(Debug, Serialize)]
+struct TrainStationBody<'a> {
+    code: &'a str,
+    name: &'a str,
+    tz: &'a str,
+    bus: bool,
+    schedule_arrival: DateTime<FixedOffset>,
+    schedule_departure: DateTime<FixedOffset>,
+    arrival: Option<DateTime<FixedOffset>>,
+    departure: Option<DateTime<FixedOffset>>,
+    arrival_comment: &'a str,
+    departure_comment: &'a str,
+    status: TrainStatus,
+}
+
+impl<'a> From<&'a Train> for TrainBody<'a> {
+    fn from(train: &'a Train) -> Self {
+        Self {
+            train_id: &train.train_id,
+            train_num: train.train_num,
+            route_name: &train.route_name,
+            lat: train.lat,
+            lon: train.lon,
+            heading: train.heading,
+            velocity: train.velocity,
+            train_timely: &train.train_timely,
+            train_state: train.train_state,
+            status_message: &train.status_message,
+            event_code: &train.event_code,
+            event_tz: train.event_tz.as_deref(),
+            event_name: train.event_name.as_deref(),
+            origin_code: &train.origin_code,
+            origin_tz: &train.origin_tz,
+            origin_name: &train.origin_name,
+            destination_code: &train.destination_code,
+            destination_tz: &train.destination_tz,
+            destination_name: &train.destination_name,
+            created_at: train.created_at,
+            updated_at: train.updated_at,
+            last_value: train.last_value,
+            object_id: train.object_id,
+            stations: train.stations.iter().map(TrainStationBody::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a TrainStation> for TrainStationBody<'a> {
+    fn from(station: &'a TrainStation) -> Self {
+        Self {
+            code: &station.code,
+            name: &station.name,
+            tz: &station.tz,
+            bus: station.bus,
+            schedule_arrival: station.schedule_arrival,
+            schedule_departure: station.schedule_departure,
+            arrival: station.arrival,
+            departure: station.departure,
+            arrival_comment: &station.arrival_comment,
+            departure_comment: &station.departure_comment,
+            status: station.status,
+        }
+    }
+}
+
+async fn get_trains(State(cache): State<Cache>) -> ApiResult {
+    let response = cached_trains(&cache)?;
+    let mut trains: Vec<_> = response.iter_trains().map(TrainBody::from).collect();
+    trains.sort_by(|a, b| a.train_id.cmp(b.train_id));
+
+    Ok(Json(trains).into_response())
+}
+
+async fn get_train(State(cache): State<Cache>, Path(id): Path<String>) -> ApiResult {
+    let response = cached_trains(&cache)?;
+
+    response
+        .get_by_id(&id)
+        .map(|train| Json(TrainBody::from(train)).into_response())
+        .ok_or(ApiError(StatusCode::NOT_FOUND, "No train with this id"))
+}
+
+async fn get_board(State(cache): State<Cache>, Path(code): Path<String>) -> ApiResult {
+    let response = cached_trains(&cache)?;
+    if let Some(stations) = cache.stations() {
+        if !stations.0.keys().any(|key| key.eq_ignore_ascii_case(&code)) {
+            return Err(ApiError(StatusCode::NOT_FOUND, "No station with this code"));
+        }
+    }
+
+    Ok(Json(response.board(&code)).into_response())
+}
+
+async fn get_geojson(State(cache): State<Cache>) -> ApiResult {
+    let response = cached_trains(&cache)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(response.to_geojson()),
+    )
+        .into_response())
+}
+
+async fn get_metrics(State(cache): State<Cache>) -> Response {
+    let mut body = cache
+        .trains()
+        .map(|trains| trains.network_metrics(Utc::now()).to_string())
+        .unwrap_or_default();
+    body.push_str(&cache.metrics.to_string());
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response()
+}
+
+/// The query parameters of the event streams, each a comma separated list
+/// named after the matching field of [`EventFilter`]
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct FilterQuery {
+    train_ids: Option<String>,
+    routes: Option<String>,
+    stations: Option<String>,
+}
+
+impl From<FilterQuery> for EventFilter {
+    fn from(query: FilterQuery) -> Self {
+        let split = |values: Option<String>| -> Vec<String> {
+            values
+                .iter()
+                .flat_map(|values| values.split(','))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        Self {
+            train_ids: split(query.train_ids),
+            routes: split(query.routes),
+            stations: split(query.stations),
+        }
+    }
+}
+
+async fn get_events(
+    State(cache): State<Cache>,
+    Query(query): Query<FilterQuery>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>> {
+    let filter = EventFilter::from(query);
+
+    // Subscribers that fall too far behind skip the events they missed
+    let stream = BroadcastStream::new(cache.subscribe()).filter_map(move |event| {
+        let event = event.ok().filter(|event| filter.matches(event))?;
+        let name = match event.kind {
+            TrainEventKind::Arrival => "arrival",
+            TrainEventKind::Departure => "departure",
+            TrainEventKind::DelayChange { .. } => "delay_change",
+        };
+
+        Some(Event::default().event(name).json_data(event))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn get_ws(
+    State(cache): State<Cache>,
+    Query(query): Query<FilterQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let events = cache.subscribe();
+
+    upgrade.on_upgrade(move |socket| stream_events(socket, events, query.into()))
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<TrainEvent>,
+    mut filter: EventFilter,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if filter.matches(&event) => {
+                    let text = serde_json::to_string(&event)
+                        .unwrap_or_else(|e| json!({ "error": e.to_string() }).to_string());
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(new_filter) => filter = new_filter,
+                    Err(e) => {
+                        let error = json!({ "error": e.to_string() }).to_string();
+                        if socket.send(Message::Text(error.into())).await.is_err() {
+                            return;
+                        }
+                    }
+                },
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => return,
+            },
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Route geometry and distance along a route
+//!
+//! A [`RouteShape`] is the polyline a route follows on the ground. Snapping a
+//! position to it gives how far along the route that position is, which is
+//! what progress math needs: the straight line distance between a train and
+//! a station is a poor estimate on a winding route.
+//!
+//! Shapes are kept in [`RouteShapes`] keyed by [`route_name`] and can be
+//! loaded from a GeoJSON `FeatureCollection` (see
+//! [`RouteShapes::from_geojson`]) or, with the `gtfs` feature, from the
+//! `shapes.txt` of a static GTFS feed (see [`GtfsFeed::route_shapes`]).
+//!
+//! All distances are expressed in miles.
+//!
+//! [`route_name`]: Train::route_name
+//! [`GtfsFeed::route_shapes`]: crate::gtfs::GtfsFeed::route_shapes
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    geo::Coordinate,
+    responses::{StationResponse, Train},
+};
+
+/// The polyline followed by a route
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteShape {
+    points: Vec<Coordinate>,
+
+    /// Distance along the shape of each point
+    distances: Vec<f64>,
+}
+
+/// A position snapped to the closest point of a [`RouteShape`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SnappedPoint {
+    /// The closest point of the shape
+    pub coordinate: Coordinate,
+
+    /// The distance along the shape from its first point
+    pub distance_along: f64,
+
+    /// The distance between the original position and the shape
+    pub offset: f64,
+}
+
+impl RouteShape {
+    /// Creates a shape from its points, in the order the route follows them
+    pub fn new(points: Vec<Coordinate>) -> Self {
+        let mut distances = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        for (index, point) in points.iter().enumerate() {
+            if index > 0 {
+                total += points[index - 1].distance_to(*point);
+            }
+            distances.push(total);
+        }
+
+        Self { points, distances }
+    }
+
+    /// Returns the points of the shape
+    pub fn points(&self) -> &[Coordinate] {
+        &self.points
+    }
+
+    /// Returns the length of the shape
+    pub fn length(&self) -> f64 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the point of the shape closest to the provided coordinate, or
+    /// `None` if the shape does not have any point.
+    pub fn snap(&self, coordinate: Coordinate) -> Option<SnappedPoint> {
+        let first = *self.points.first()?;
+        let mut best = SnappedPoint {
+            coordinate: first,
+            distance_along: 0.0,
+            offset: coordinate.distance_to(first),
+        };
+
+        for (index, segment) in self.points.windows(2).enumerate() {
+            let fraction = project(coordinate, segment[0], segment[1]);
+            let snapped = Coordinate::new(
+                segment[0].lat + (segment[1].lat - segment[0].lat) * fraction,
+                segment[0].lon + (segment[1].lon - segment[0].lon) * fraction,
+            );
+
+            let offset = coordinate.distance_to(snapped);
+            if offset < best.offset {
+                best = SnappedPoint {
+                    coordinate: snapped,
+                    distance_along: self.distances[index]
+                        + (self.distances[index + 1] - self.distances[index]) * fraction,
+                    offset,
+                };
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Returns the distance along the shape of the point closest to the
+    /// provided coordinate
+    pub fn distance_along(&self, coordinate: Coordinate) -> Option<f64> {
+        self.snap(coordinate).map(|snapped| snapped.distance_along)
+    }
+}
+
+/// Returns where the projection of `point` falls on the segment from `start`
+/// to `end`, between `0.0` and `1.0`, using an equirectangular approximation
+/// which is accurate enough for the short segments of a shape.
+fn project(point: Coordinate, start: Coordinate, end: Coordinate) -> f64 {
+    let scale = start.lat.to_radians().cos();
+    let (dx, dy) = ((end.lon - start.lon) * scale, end.lat - start.lat);
+    let (px, py) = ((point.lon - start.lon) * scale, point.lat - start.lat);
+
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return 0.0;
+    }
+
+    ((px * dx + py * dy) / length).clamp(0.0, 1.0)
+}
+
+/// Route shapes keyed by [`route_name`]
+///
+/// [`route_name`]: Train::route_name
+#[derive(Debug, Clone, Default)]
+pub struct RouteShapes(HashMap<String, RouteShape>);
+
+impl RouteShapes {
+    /// Creates an empty set of shapes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the shape of a route, replacing any previous one
+    pub fn insert<S: Into<String>>(&mut self, route_name: S, shape: RouteShape) {
+        self.0.insert(route_name.into(), shape);
+    }
+
+    /// Returns the shape of the route with the provided name
+    pub fn get(&self, route_name: &str) -> Option<&RouteShape> {
+        self.0.get(route_name)
+    }
+
+    /// Returns the number of routes with a shape
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no shapes
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Loads the shapes of a GeoJSON `FeatureCollection`
+    ///
+    /// Every `LineString` feature with a `routeName` property becomes the
+    /// shape of that route, which is the format of
+    /// [`Train::route_to_geojson`]. Other features are ignored.
+    pub fn from_geojson(geojson: &Value) -> Self {
+        let features = geojson["features"].as_array().into_iter().flatten();
+
+        Self(
+            features
+                .filter(|feature| feature["geometry"]["type"] == "LineString")
+                .filter_map(|feature| {
+                    let route_name = feature["properties"]["routeName"].as_str()?;
+                    let points = feature["geometry"]["coordinates"]
+                        .as_array()?
+                        .iter()
+                        .map(|position| {
+                            Some(Coordinate::new(
+                                position[1].as_f64()?,
+                                position[0].as_f64()?,
+                            ))
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+
+                    Some((route_name.to_string(), RouteShape::new(points)))
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The distance between a train and one of its stations along the route
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationDistance {
+    /// The code of the station
+    pub station_code: String,
+
+    /// The distance from the train to the station, negative for stations
+    /// behind the train
+    pub distance: f64,
+}
+
+/// The progress of a train along the shape of its route
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteProgress {
+    /// The position of the train snapped to the shape
+    pub snapped: SnappedPoint,
+
+    /// The distance travelled since the origin
+    pub distance_travelled: f64,
+
+    /// The distance left to the destination
+    pub distance_remaining: f64,
+
+    /// The distance to each of the stations of the train that could be
+    /// located, in the order the train stops at them
+    pub stations: Vec<StationDistance>,
+}
+
+impl Train {
+    /// Returns the progress of this train along the shape of its route, or
+    /// `None` if `shapes` does not contain its route.
+    ///
+    /// Station coordinates are looked up in `stations`. The origin and
+    /// destination stations tell in which direction the shape is followed,
+    /// when either cannot be located the ends of the shape are used instead
+    /// and the shape is assumed to start at the origin.
+    pub fn progress(
+        &self,
+        shapes: &RouteShapes,
+        stations: &StationResponse,
+    ) -> Option<RouteProgress> {
+        let shape = shapes.get(&self.route_name)?;
+        let snapped = shape.snap(self.coordinate())?;
+        let distance_along = |code: &str| {
+            stations
+                .0
+                .get(code)
+                .and_then(|station| shape.distance_along(station.coordinate()))
+        };
+
+        let (origin, destination) = match (
+            self.stations
+                .first()
+                .and_then(|station| distance_along(&station.code)),
+            self.stations
+                .last()
+                .and_then(|station| distance_along(&station.code)),
+        ) {
+            (Some(origin), Some(destination)) => (origin, destination),
+            _ => (0.0, shape.length()),
+        };
+        let direction = if destination < origin { -1.0 } else { 1.0 };
+
+        Some(RouteProgress {
+            snapped,
+            distance_travelled: ((snapped.distance_along - origin) * direction).max(0.0),
+            distance_remaining: ((destination - snapped.distance_along) * direction).max(0.0),
+            stations: self
+                .stations
+                .iter()
+                .filter_map(|station| {
+                    Some(StationDistance {
+                        station_code: station.code.clone(),
+                        distance: (distance_along(&station.code)? - snapped.distance_along)
+                            * direction,
+                    })
+                })
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,480 @@
+//! Persistent history backed by SQLite
+//!
+//! The API only reports the current state of the network. A [`Store`] keeps
+//! every [`TrainResponse`] snapshot ingested into it so the history of a train
+//! can be queried later on. The database holds three tables:
+//!
+//! * `trains` - Every distinct snapshot of a train, de-duplicated on its
+//!   [`object_id`] and [`updated_at`]
+//! * `stop_events` - The latest known [`StopEvent`] of every run at each of
+//!   its stations
+//! * `positions` - The reported position of the train in every snapshot
+//!
+//! A run is a single trip of a train. Since [`train_id`]s are reused every
+//! month, a run is identified by its [`train_id`] together with the scheduled
+//! departure from its origin.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use amtrak_api::{storage::Store, Client};
+//! use chrono::{Duration, Utc};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mut store = Store::open("history.sqlite")?;
+//!     store.ingest(&Client::new().trains().await?)?;
+//!
+//!     for run in store.runs(657, Utc::now() - Duration::days(30))? {
+//!         println!("{} departed {}", run.train_id, run.departure);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`object_id`]: Train::object_id
+//! [`updated_at`]: Train::updated_at
+//! [`train_id`]: Train::train_id
+
+use std::path::Path;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{params, types::Type, Connection, OpenFlags, OptionalExtension, Row};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    errors,
+    export::StopEvent,
+    geo::Coordinate,
+    responses::{Heading, Train, TrainResponse},
+};
+
+pub type Result<T> = std::result::Result<T, errors::Error>;
+
+/// The schema migrations, the database's `user_version` is the number of
+/// migrations that have been applied to it. Migrations are append only.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE trains (
+        id INTEGER PRIMARY KEY,
+        object_id INTEGER NOT NULL,
+        train_id TEXT NOT NULL,
+        train_num INTEGER NOT NULL,
+        route_name TEXT NOT NULL,
+        run_departure INTEGER NOT NULL,
+        train_state TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        snapshot TEXT NOT NULL,
+        UNIQUE (object_id, updated_at)
+    );
+    CREATE INDEX trains_run ON trains (train_num, run_departure);
+
+    CREATE TABLE stop_events (
+        train_id TEXT NOT NULL,
+        run_departure INTEGER NOT NULL,
+        stop_sequence INTEGER NOT NULL,
+        train_num INTEGER NOT NULL,
+        route_name TEXT NOT NULL,
+        station_code TEXT NOT NULL,
+        scheduled_arrival INTEGER NOT NULL,
+        scheduled_departure INTEGER NOT NULL,
+        actual_arrival INTEGER,
+        actual_departure INTEGER,
+        arrival_delay_minutes INTEGER,
+        departure_delay_minutes INTEGER,
+        status TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        PRIMARY KEY (train_id, run_departure, stop_sequence)
+    );
+    CREATE INDEX stop_events_station ON stop_events (station_code, scheduled_arrival);
+
+    CREATE TABLE positions (
+        snapshot_id INTEGER PRIMARY KEY REFERENCES trains (id),
+        train_id TEXT NOT NULL,
+        recorded_at INTEGER NOT NULL,
+        lat REAL NOT NULL,
+        lon REAL NOT NULL,
+        heading TEXT NOT NULL,
+        velocity REAL NOT NULL
+    );
+    CREATE INDEX positions_train ON positions (train_id, recorded_at);
+"];
+
+/// The outcome of [`Store::ingest`]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct IngestSummary {
+    /// Number of train snapshots that were stored
+    pub inserted: usize,
+
+    /// Number of train snapshots that were already stored and were skipped
+    pub duplicates: usize,
+}
+
+/// A single trip of a train
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// The [`train_num`] of the train
+    ///
+    /// [`train_num`]: Train::train_num
+    pub train_num: u32,
+
+    /// The [`route_name`] of the train
+    ///
+    /// [`route_name`]: Train::route_name
+    pub route_name: String,
+
+    /// The scheduled departure from the origin station
+    pub departure: DateTime<Utc>,
+
+    /// The most recent snapshot of the train during this run
+    pub train: Train,
+
+    /// The latest known event at each station of the run, in the order the
+    /// train stops at them
+    pub stop_events: Vec<StopEvent>,
+}
+
+/// A reported position of a train
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// The [`train_id`] of the train
+    ///
+    /// [`train_id`]: Train::train_id
+    pub train_id: String,
+
+    /// When the position was reported, from [`last_value`]
+    ///
+    /// [`last_value`]: Train::last_value
+    pub recorded_at: DateTime<Utc>,
+
+    /// Where the train was
+    pub coordinate: Coordinate,
+
+    /// The direction the train was heading in
+    pub heading: Heading,
+
+    /// The speed of the train in miles per hour
+    pub velocity: f32,
+}
+
+/// A history of train snapshots stored in a SQLite database
+#[derive(Debug)]
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    /// Opens (or creates) the database at the provided path and migrates it
+    /// to the latest schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// Opens the existing database at the provided path and migrates it to
+    /// the latest schema, failing instead of creating the database when it
+    /// does not exist.
+    pub fn open_existing<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut flags = OpenFlags::default();
+        flags.remove(OpenFlags::SQLITE_OPEN_CREATE);
+
+        Self::new(Connection::open_with_flags(path, flags)?)
+    }
+
+    /// Creates a new database that only lives in memory, useful for testing
+    pub fn open_in_memory() -> Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(mut connection: Connection) -> Result<Self> {
+        let version = schema_version(&connection)?;
+
+        let transaction = connection.transaction()?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", index + 1)?;
+        }
+        transaction.commit()?;
+
+        Ok(Self { connection })
+    }
+
+    /// Returns the version of the schema of the database
+    pub fn schema_version(&self) -> Result<u32> {
+        schema_version(&self.connection)
+    }
+
+    /// Stores every train of the response. Snapshots of a train that were
+    /// already ingested (same [`object_id`] and [`updated_at`]) are skipped.
+    ///
+    /// [`object_id`]: Train::object_id
+    /// [`updated_at`]: Train::updated_at
+    pub fn ingest(&mut self, response: &TrainResponse) -> Result<IngestSummary> {
+        let mut summary = IngestSummary::default();
+        let transaction = self.connection.transaction()?;
+
+        for train in response.iter_trains() {
+            let run_departure = run_departure(train);
+            let updated_at = train.updated_at.timestamp();
+
+            let inserted = transaction.execute(
+                "INSERT OR IGNORE INTO trains (object_id, train_id, train_num, route_name,
+                     run_departure, train_state, updated_at, snapshot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    train.object_id,
+                    train.train_id,
+                    train.train_num,
+                    train.route_name,
+                    run_departure,
+                    to_text(&train.train_state)?,
+                    updated_at,
+                    serde_json::to_string(train)?,
+                ],
+            )?;
+            if inserted == 0 {
+                summary.duplicates += 1;
+                continue;
+            }
+            summary.inserted += 1;
+
+            transaction.execute(
+                "INSERT INTO positions (snapshot_id, train_id, recorded_at, lat, lon, heading,
+                     velocity)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    transaction.last_insert_rowid(),
+                    train.train_id,
+                    train.last_value.timestamp(),
+                    train.lat,
+                    train.lon,
+                    to_text(&train.heading)?,
+                    train.velocity,
+                ],
+            )?;
+
+            // Snapshots can be ingested out of order, only newer snapshots
+            // replace the known stop events
+            let mut statement = transaction.prepare_cached(
+                "INSERT INTO stop_events (train_id, run_departure, stop_sequence, train_num,
+                     route_name, station_code, scheduled_arrival, scheduled_departure,
+                     actual_arrival, actual_departure, arrival_delay_minutes,
+                     departure_delay_minutes, status, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                 ON CONFLICT (train_id, run_departure, stop_sequence) DO UPDATE SET
+                     station_code = excluded.station_code,
+                     scheduled_arrival = excluded.scheduled_arrival,
+                     scheduled_departure = excluded.scheduled_departure,
+                     actual_arrival = excluded.actual_arrival,
+                     actual_departure = excluded.actual_departure,
+                     arrival_delay_minutes = excluded.arrival_delay_minutes,
+                     departure_delay_minutes = excluded.departure_delay_minutes,
+                     status = excluded.status,
+                     updated_at = excluded.updated_at
+                 WHERE excluded.updated_at >= stop_events.updated_at",
+            )?;
+            for event in train.stop_events() {
+                statement.execute(params![
+                    event.train_id,
+                    run_departure,
+                    event.stop_sequence,
+                    event.train_num,
+                    event.route_name,
+                    event.station_code,
+                    event.scheduled_arrival.timestamp(),
+                    event.scheduled_departure.timestamp(),
+                    event.actual_arrival.map(|time| time.timestamp()),
+                    event.actual_departure.map(|time| time.timestamp()),
+                    event.arrival_delay_minutes,
+                    event.departure_delay_minutes,
+                    to_text(&event.status)?,
+                    updated_at,
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(summary)
+    }
+
+    /// Returns every run of the train number that departed its origin at or
+    /// after `since`, oldest first.
+    pub fn runs<Tz: TimeZone>(&self, train_num: u32, since: DateTime<Tz>) -> Result<Vec<Run>> {
+        self.query_runs(Some(train_num), since.timestamp())
+    }
+
+    /// Returns every run of every train that departed its origin at or after
+    /// `since`, oldest first.
+    pub fn all_runs<Tz: TimeZone>(&self, since: DateTime<Tz>) -> Result<Vec<Run>> {
+        self.query_runs(None, since.timestamp())
+    }
+
+    fn query_runs(&self, train_num: Option<u32>, since: i64) -> Result<Vec<Run>> {
+        // SQLite returns the snapshot of the row holding the maximum
+        let snapshots = self
+            .connection
+            .prepare_cached(
+                "SELECT run_departure, snapshot, MAX(updated_at) FROM trains
+                 WHERE (?1 IS NULL OR train_num = ?1) AND run_departure >= ?2
+                 GROUP BY train_id, run_departure
+                 ORDER BY run_departure, train_id",
+            )?
+            .query_map(params![train_num, since], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        snapshots
+            .into_iter()
+            .map(|(departure, snapshot)| {
+                let train: Train = serde_json::from_str(&snapshot)?;
+
+                Ok(Run {
+                    train_id: train.train_id.clone(),
+                    train_num: train.train_num,
+                    route_name: train.route_name.clone(),
+                    departure: timestamp(departure),
+                    stop_events: self.stop_events(&train.train_id, departure)?,
+                    train,
+                })
+            })
+            .collect()
+    }
+
+    fn stop_events(&self, train_id: &str, run_departure: i64) -> Result<Vec<StopEvent>> {
+        let events = self
+            .connection
+            .prepare_cached(
+                "SELECT train_id, train_num, route_name, stop_sequence, station_code,
+                     scheduled_arrival, scheduled_departure, actual_arrival, actual_departure,
+                     arrival_delay_minutes, departure_delay_minutes, status
+                 FROM stop_events
+                 WHERE train_id = ?1 AND run_departure = ?2
+                 ORDER BY stop_sequence",
+            )?
+            .query_map(params![train_id, run_departure], |row| {
+                Ok(StopEvent {
+                    train_id: row.get(0)?,
+                    train_num: row.get(1)?,
+                    route_name: row.get(2)?,
+                    stop_sequence: row.get(3)?,
+                    station_code: row.get(4)?,
+                    scheduled_arrival: timestamp(row.get(5)?),
+                    scheduled_departure: timestamp(row.get(6)?),
+                    actual_arrival: row.get::<_, Option<i64>>(7)?.map(timestamp),
+                    actual_departure: row.get::<_, Option<i64>>(8)?.map(timestamp),
+                    arrival_delay_minutes: row.get(9)?,
+                    departure_delay_minutes: row.get(10)?,
+                    status: from_column(row, 11)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(events)
+    }
+
+    /// Returns the positions reported by the train at or after `since`,
+    /// oldest first.
+    pub fn positions<Tz: TimeZone>(
+        &self,
+        train_id: &str,
+        since: DateTime<Tz>,
+    ) -> Result<Vec<Position>> {
+        let positions = self
+            .connection
+            .prepare_cached(
+                "SELECT recorded_at, lat, lon, heading, velocity FROM positions
+                 WHERE train_id = ?1 AND recorded_at >= ?2
+                 ORDER BY recorded_at",
+            )?
+            .query_map(params![train_id, since.timestamp()], |row| {
+                Ok(Position {
+                    train_id: train_id.to_string(),
+                    recorded_at: timestamp(row.get(0)?),
+                    coordinate: Coordinate::new(row.get(1)?, row.get(2)?),
+                    heading: from_column(row, 3)?,
+                    velocity: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(positions)
+    }
+
+    /// Returns every stored snapshot of the run, oldest first
+    pub fn snapshots(&self, run: &Run) -> Result<Vec<Train>> {
+        let snapshots = self
+            .connection
+            .prepare_cached(
+                "SELECT snapshot FROM trains WHERE train_id = ?1 AND run_departure = ?2
+                 ORDER BY updated_at",
+            )?
+            .query_map(params![run.train_id, run.departure.timestamp()], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        snapshots
+            .iter()
+            .map(|snapshot| Ok(serde_json::from_str(snapshot)?))
+            .collect()
+    }
+
+    /// Returns the most recent snapshot of the train with the provided
+    /// [`train_id`], if any was ingested.
+    ///
+    /// [`train_id`]: Train::train_id
+    pub fn latest(&self, train_id: &str) -> Result<Option<Train>> {
+        let snapshot: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT snapshot FROM trains WHERE train_id = ?1
+                 ORDER BY updated_at DESC LIMIT 1",
+                params![train_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(snapshot
+            .map(|snapshot| serde_json::from_str(&snapshot))
+            .transpose()?)
+    }
+}
+
+fn schema_version(connection: &Connection) -> Result<u32> {
+    Ok(connection.pragma_query_value(None, "user_version", |row| row.get(0))?)
+}
+
+/// Returns the scheduled departure from the origin that identifies the run of
+/// the train, falling back to the creation of the train entry.
+fn run_departure(train: &Train) -> i64 {
+    train
+        .stations
+        .first()
+        .map_or(train.created_at, |station| station.schedule_departure)
+        .timestamp()
+}
+
+fn timestamp(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+}
+
+/// Stores a unit enum (such as [`Heading`]) using its serialized name
+fn to_text<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(text) => Ok(text),
+        value => Ok(value.to_string()),
+    }
+}
+
+/// Reads back a unit enum stored with [`to_text`]
+fn from_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use amtrak_api::address::{Country, StateOrProvince};
+
+#[test]
+fn test_parse_state_or_province() {
+    assert_eq!("PA".parse(), Ok(StateOrProvince::Pennsylvania));
+    assert_eq!(" pa ".parse(), Ok(StateOrProvince::Pennsylvania));
+    assert_eq!("new york".parse(), Ok(StateOrProvince::NewYork));
+    assert_eq!("ON".parse(), Ok(StateOrProvince::Ontario));
+    assert_eq!("DC".parse(), Ok(StateOrProvince::DistrictOfColumbia));
+
+    let error = "Atlantis".parse::<StateOrProvince>().unwrap_err();
+    assert_eq!(error.to_string(), "Unknown state or province \"Atlantis\"");
+
+    assert_eq!(StateOrProvince::ALL.len(), 64);
+    for state in StateOrProvince::ALL {
+        assert_eq!(state.code().parse(), Ok(*state));
+        assert_eq!(state.name().parse(), Ok(*state));
+        assert_eq!(state.to_string(), state.code());
+    }
+}
+
+#[test]
+fn test_country() {
+    assert_eq!(StateOrProvince::California.country(), Country::UnitedStates);
+    assert_eq!(StateOrProvince::Quebec.country(), Country::Canada);
+
+    // Both serialize as their variant name
+    assert_eq!(
+        serde_json::to_string(&StateOrProvince::California).unwrap(),
+        "\"California\""
+    );
+    assert_eq!(
+        serde_json::to_string(&Country::UnitedStates).unwrap(),
+        "\"UnitedStates\""
+    );
+}
+
+#[test]
+fn test_postal_codes() {
+    let us = Country::UnitedStates;
+    assert!(us.is_valid_postal_code("19104"));
+    assert!(us.is_valid_postal_code("19104-2801"));
+    assert!(us.is_valid_postal_code(" 07102 "));
+    assert!(!us.is_valid_postal_code("1910"));
+    assert!(!us.is_valid_postal_code("19104-28"));
+    assert!(!us.is_valid_postal_code("1910A"));
+    assert!(!us.is_valid_postal_code("M5J 1E6"));
+
+    let canada = Country::Canada;
+    assert!(canada.is_valid_postal_code("M5J 1E6"));
+    assert!(canada.is_valid_postal_code("M5J1E6"));
+    assert!(!canada.is_valid_postal_code("m5j 1e6"));
+    assert!(!canada.is_valid_postal_code("M5J  1E6"));
+    assert!(!canada.is_valid_postal_code("19104"));
+}
+
+#[test]
+fn test_address() {
+    let stations = common::stations();
+
+    let philadelphia = stations.0["PHL"].address();
+    assert_eq!(philadelphia.lines, ["2955 Market Street"]);
+    assert_eq!(
+        philadelphia.single_line(),
+        "2955 Market Street, Philadelphia, PA 19104"
+    );
+    assert_eq!(
+        philadelphia.multiline(),
+        "2955 Market Street\nPhiladelphia, PA 19104"
+    );
+    assert_eq!(philadelphia.to_string(), philadelphia.single_line());
+    assert_eq!(
+        philadelphia.state_or_province(),
+        Some(StateOrProvince::Pennsylvania)
+    );
+    assert!(philadelphia.is_zip_valid());
+
+    let toronto = stations.0["TWO"].address();
+    assert_eq!(toronto.country(), Some(Country::Canada));
+    assert!(toronto.is_zip_valid());
+    assert_eq!(
+        toronto.single_line(),
+        "65 Front Street West, Toronto, ON M5J 1E6"
+    );
+
+    let mut invalid = toronto.clone();
+    invalid.zip = "19104".to_string();
+    assert!(!invalid.is_zip_valid());
+    invalid.state = "Atlantis".to_string();
+    assert_eq!(invalid.country(), None);
+    assert!(invalid.is_zip_valid());
+}
+
+#[test]
+fn test_stations_in_state() {
+    let stations = common::stations();
+
+    let mut pennsylvania: Vec<_> = stations
+        .in_state(StateOrProvince::Pennsylvania)
+        .map(|station| station.code.as_str())
+        .collect();
+    pennsylvania.sort();
+    assert_eq!(pennsylvania, ["ARD", "HAR", "LNC", "PAO", "PGH", "PHL"]);
+
+    let canada: Vec<_> = stations
+        .in_country(Country::Canada)
+        .map(|station| station.code.as_str())
+        .collect();
+    assert_eq!(canada, ["TWO"]);
+    assert_eq!(stations.in_country(Country::UnitedStates).count(), 17);
+    assert_eq!(stations.in_state(StateOrProvince::Texas).count(), 0);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use amtrak_api::{
+    analytics::Report,
+    responses::TrainResponse,
+    storage::{Run, Store},
+};
+use chrono::{Duration, Weekday};
+
+fn runs(responses: &[TrainResponse]) -> Vec<Run> {
+    let mut store = Store::open_in_memory().unwrap();
+    for response in responses {
+        store.ingest(response).unwrap();
+    }
+
+    store.all_runs(common::now() - Duration::days(30)).unwrap()
+}
+
+#[test]
+fn test_overall() {
+    let runs = runs(&[common::trains(), common::later_trains()]);
+    let report = Report::from_runs(&runs, Report::DEFAULT_THRESHOLD_MINUTES);
+    let overall = report.overall.unwrap();
+
+    // Only the stations the trains actually reached count
+    assert_eq!(overall.samples, 17);
+    assert_eq!(overall.on_time, 14);
+    assert_eq!(overall.p50_delay_minutes, 5);
+    assert_eq!(overall.p90_delay_minutes, 20);
+    assert_eq!(overall.max_delay_minutes, 20);
+
+    let strict = Report::from_runs(&runs, 2).overall.unwrap();
+    assert_eq!(strict.on_time, 7);
+}
+
+#[test]
+fn test_groups() {
+    let report = Report::from_runs(
+        &runs(&[common::trains(), common::later_trains()]),
+        Report::DEFAULT_THRESHOLD_MINUTES,
+    );
+
+    let routes: Vec<_> = report
+        .by_route
+        .iter()
+        .map(|group| (group.key.as_str(), group.summary.on_time_performance))
+        .collect();
+    assert_eq!(
+        routes,
+        vec![
+            ("Capitol Corridor", 1.0),
+            ("Keystone", 1.0),
+            ("Northeast Regional", 0.0)
+        ]
+    );
+
+    let train_nums: Vec<_> = report
+        .by_train_num
+        .iter()
+        .map(|group| (group.key, group.summary.samples))
+        .collect();
+    assert_eq!(train_nums, vec![(94, 3), (538, 3), (612, 5), (657, 6)]);
+
+    let okj = report
+        .by_station
+        .iter()
+        .find(|group| group.key == "OKJ")
+        .unwrap();
+    assert_eq!(okj.summary.mean_delay_minutes, -1.0);
+
+    // The Pennsylvanian has not departed yet
+    assert!(report.by_station.iter().all(|group| group.key != "PGH"));
+}
+
+#[test]
+fn test_weekdays() {
+    let mut monday = common::trains();
+    for train in monday.0.values_mut().flatten() {
+        let shift = -Duration::days(1);
+        train.object_id += 1000;
+        train.updated_at += shift;
+        for station in &mut train.stations {
+            station.schedule_arrival += shift;
+            station.schedule_departure += shift;
+            station.arrival = station.arrival.map(|arrival| arrival + shift);
+            station.departure = station.departure.map(|departure| departure + shift);
+        }
+    }
+
+    let report = Report::from_runs(
+        &runs(&[monday, common::trains()]),
+        Report::DEFAULT_THRESHOLD_MINUTES,
+    );
+    let weekdays: Vec<_> = report
+        .by_weekday
+        .iter()
+        .map(|group| (group.key, group.summary.samples))
+        .collect();
+    assert_eq!(weekdays, vec![(Weekday::Mon, 15), (Weekday::Tue, 15)]);
+}
+
+#[test]
+fn test_segments() {
+    let report = Report::from_runs(
+        &runs(&[common::trains(), common::later_trains()]),
+        Report::DEFAULT_THRESHOLD_MINUTES,
+    );
+
+    let worst = &report.segments[0];
+    assert_eq!(worst.route_name, "Capitol Corridor");
+    assert_eq!(
+        (worst.from_station.as_str(), worst.to_station.as_str()),
+        ("OKJ", "EMY")
+    );
+    assert_eq!(worst.mean_delay_introduced_minutes, 3.0);
+
+    let wilmington = report
+        .segments
+        .iter()
+        .find(|segment| segment.to_station == "WIL")
+        .unwrap();
+    assert_eq!(wilmington.from_station, "BAL");
+    assert_eq!(wilmington.mean_delay_introduced_minutes, -2.0);
+}
+
+#[test]
+fn test_empty() {
+    let report = Report::from_runs(&[], Report::DEFAULT_THRESHOLD_MINUTES);
+
+    assert!(report.overall.is_none());
+    assert!(report.by_route.is_empty());
+    assert!(report.segments.is_empty());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use amtrak_api::{board::DelaySeverity, responses::TrainStatus};
+use chrono::Duration;
+
+#[test]
+fn test_board() {
+    let board = common::trains().board("PHL");
+    assert_eq!(board.station_code, "PHL");
+
+    // The Keystone 657 already departed Philadelphia and the Capitol Corridor
+    // does not stop there
+    let trains: Vec<_> = board
+        .entries
+        .iter()
+        .map(|entry| entry.train_id.as_str())
+        .collect();
+    assert_eq!(trains, vec!["612-29", "94-29", "43-30"]);
+
+    let entry = &board.entries[1];
+    assert_eq!(entry.destination_name, "New York Penn");
+    assert_eq!(entry.arrival_comment, "20 Minutes Late");
+    assert_eq!(entry.delay_minutes, Some(20));
+    assert_eq!(entry.status, TrainStatus::Enroute);
+    assert_eq!(
+        entry.expected_departure().to_rfc3339(),
+        "2023-08-29T23:08:00-04:00"
+    );
+
+    assert!(common::trains().board("XYZ").entries.is_empty());
+}
+
+#[test]
+fn test_delay_severity() {
+    let board = common::trains().board("PHL");
+    let severities: Vec<_> = board.entries.iter().map(|entry| entry.severity()).collect();
+    assert_eq!(
+        severities,
+        vec![
+            DelaySeverity::OnTime,
+            DelaySeverity::Major,
+            DelaySeverity::Unknown
+        ]
+    );
+
+    assert_eq!(
+        DelaySeverity::from_delay(Some(Duration::minutes(-3))),
+        DelaySeverity::OnTime
+    );
+    assert_eq!(
+        DelaySeverity::from_delay(Some(Duration::minutes(5))),
+        DelaySeverity::OnTime
+    );
+    assert_eq!(
+        DelaySeverity::from_delay(Some(Duration::minutes(6))),
+        DelaySeverity::Minor
+    );
+    assert_eq!(
+        DelaySeverity::from_delay(Some(Duration::minutes(15))),
+        DelaySeverity::Minor
+    );
+    assert_eq!(
+        DelaySeverity::from_delay(Some(Duration::minutes(16))),
+        DelaySeverity::Major
+    );
+}
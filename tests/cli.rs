@@ -0,0 +1,338 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use mockito::Server;
+use std::process::{Command, Output, Stdio};
+
+fn amtrak(server: &Server, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_amtrak"))
+        .arg("--base-url")
+        .arg(server.url())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_trains_table() {
+    let mut server = Server::new();
+    let mock_server = server
+        .mock("GET", "/trains")
+        .with_body(common::TRAINS_JSON)
+        .create();
+
+    let output = amtrak(&server, &["trains", "--route", "keystone"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("TRAIN"));
+    assert!(lines[1].starts_with("612-29"));
+    assert!(lines[2].starts_with("657-29"));
+    assert!(lines[2].contains("PAO 22:34"));
+
+    mock_server.assert();
+}
+
+#[test]
+fn test_train_json() {
+    let mut server = Server::new();
+    let body = format!(
+        "{{\"94\": {}}}",
+        serde_json::to_string(&common::trains().0["94"]).unwrap()
+    );
+    let mock_server = server.mock("GET", "/trains/94").with_body(body).create();
+
+    let output = amtrak(&server, &["--format", "json", "train", "94"]);
+    assert!(output.status.success());
+
+    let trains: Vec<amtrak_api::responses::Train> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(trains.len(), 1);
+    assert_eq!(trains[0].train_id, "94-29");
+    assert_eq!(trains[0].stations.len(), 7);
+
+    mock_server.assert();
+}
+
+#[test]
+fn test_stations_csv() {
+    let mut server = Server::new();
+    let mock_server = server
+        .mock("GET", "/stations")
+        .with_body(common::STATIONS_JSON)
+        .create();
+
+    let output = amtrak(&server, &["stations", "--state", "CA", "-f", "csv"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        "code,name,address1,address2,city,state,zip,tz,lat,lon,trains"
+    );
+    assert_eq!(lines.len(), 6);
+    assert_eq!(
+        lines[1],
+        "EMY,Emeryville,5885 Horton Street,,Emeryville,CA,94608,America/Los_Angeles,37.8405,-122.2918,538-29"
+    );
+
+    mock_server.assert();
+}
+
+#[test]
+fn test_stations_state_name() {
+    let mut server = Server::new();
+    let mock_server = server
+        .mock("GET", "/stations")
+        .with_body(common::STATIONS_JSON)
+        .create();
+
+    let output = amtrak(&server, &["stations", "--state", "california", "-f", "csv"]);
+    assert!(output.status.success());
+
+    // The full name selects the same stations as the abbreviation
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines[1].starts_with("EMY,"));
+
+    mock_server.assert();
+}
+
+#[test]
+fn test_board() {
+    let mut server = Server::new();
+    let stations = common::stations();
+    let trains = common::trains();
+
+    // The station code is matched ignoring case
+    let station_mocks: Vec<_> = ["PHL", "phl"]
+        .iter()
+        .map(|code| {
+            server
+                .mock("GET", format!("/stations/{}", code).as_str())
+                .with_body(format!(
+                    "{{\"PHL\": {}}}",
+                    serde_json::to_string(&stations.0["PHL"]).unwrap()
+                ))
+                .create()
+        })
+        .collect();
+    let train_mocks: Vec<_> = trains
+        .0
+        .values()
+        .flatten()
+        .filter(|train| stations.0["PHL"].trains.contains(&train.train_id))
+        .map(|train| {
+            server
+                .mock("GET", format!("/trains/{}", train.train_id).as_str())
+                .with_body(format!(
+                    "{{\"{}\": [{}]}}",
+                    train.train_num,
+                    serde_json::to_string(train).unwrap()
+                ))
+                .expect(2)
+                .create()
+        })
+        .collect();
+
+    for code in ["PHL", "phl"] {
+        let output = amtrak(&server, &["board", code]);
+        assert!(output.status.success());
+
+        // The Keystone 657 already departed Philadelphia
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let trains: Vec<_> = stdout
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect();
+        assert_eq!(trains, vec!["612-29", "94-29", "43-30"], "{}", code);
+    }
+
+    station_mocks.iter().for_each(|mock| mock.assert());
+    train_mocks.iter().for_each(|mock| mock.assert());
+}
+
+#[test]
+fn test_board_unavailable_train() {
+    let mut server = Server::new();
+    let stations = common::stations();
+    let trains = common::trains();
+
+    server
+        .mock("GET", "/stations/PHL")
+        .with_body(format!(
+            "{{\"PHL\": {}}}",
+            serde_json::to_string(&stations.0["PHL"]).unwrap()
+        ))
+        .create();
+    for train in trains
+        .0
+        .values()
+        .flatten()
+        .filter(|train| stations.0["PHL"].trains.contains(&train.train_id))
+    {
+        let mock = server.mock("GET", format!("/trains/{}", train.train_id).as_str());
+        match train.train_id.as_str() {
+            "94-29" => mock.with_status(500).create(),
+            _ => mock
+                .with_body(format!(
+                    "{{\"{}\": [{}]}}",
+                    train.train_num,
+                    serde_json::to_string(train).unwrap()
+                ))
+                .create(),
+        };
+    }
+
+    // The other trains are still listed when one of them cannot be fetched
+    let output = amtrak(&server, &["board", "PHL"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let trains: Vec<_> = stdout
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(trains, vec!["612-29", "43-30"]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Train \"94-29\" could not be fetched"));
+}
+
+#[test]
+fn test_exit_codes() {
+    let mut server = Server::new();
+    server.mock("GET", "/trains/1").with_body("[]").create();
+    server
+        .mock("GET", "/stations/XYZ")
+        .with_body("not json")
+        .create();
+
+    let output = amtrak(&server, &["train", "1"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("is not currently in the Amtrak network"));
+
+    let output = amtrak(&server, &["station", "XYZ"]);
+    assert_eq!(output.status.code(), Some(5));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_amtrak"))
+        .args(["--base-url", "http://127.0.0.1:1", "trains"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+
+    let output = amtrak(&server, &["frobnicate"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = amtrak(&server, &["stations", "--state", "Atlantis"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown state or province"));
+
+    // Writing to a closed stdout, as in `amtrak trains | head -1`
+    server
+        .mock("GET", "/trains")
+        .with_body(common::TRAINS_JSON)
+        .create();
+    for format in ["table", "json"] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_amtrak"))
+            .args(["--base-url", &server.url(), "--format", format, "trains"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        drop(child.stdout.take());
+        assert_eq!(child.wait().unwrap().code(), Some(7), "{}", format);
+    }
+}
+
+#[test]
+fn test_watch_keeps_polling_after_an_error() {
+    let mut server = Server::new();
+    let mock_server = server
+        .mock("GET", "/trains")
+        .with_status(500)
+        .expect_at_least(2)
+        .create();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_amtrak"))
+        .args(["--base-url", &server.url(), "watch", "--interval", "1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.lines().count() >= 2);
+    assert!(stderr.lines().all(|line| line.starts_with("amtrak: ")));
+
+    mock_server.assert();
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_report() {
+    let path = std::env::temp_dir().join(format!("amtrak-cli-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut store = amtrak_api::storage::Store::open(&path).unwrap();
+    store.ingest(&common::trains()).unwrap();
+    drop(store);
+
+    let server = Server::new();
+    let report = |format: &str| {
+        amtrak(
+            &server,
+            &[
+                "--format",
+                format,
+                "report",
+                "--database",
+                path.to_str().unwrap(),
+                "--days",
+                "36500",
+                "--route",
+                "keystone",
+            ],
+        )
+    };
+
+    let output = report("json");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["overall"]["samples"], 10);
+    assert_eq!(json["by_route"][0]["key"], "Keystone");
+
+    let output = report("csv");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].starts_with("group,key,samples,on_time,on_time_performance"));
+    assert!(lines[1].starts_with("overall,all,10,10,1.0"));
+
+    let output = report("table");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("route    Keystone"));
+    assert!(stdout.contains("DELAY INTRODUCED"));
+
+    std::fs::remove_file(&path).unwrap();
+
+    // A missing database is reported as a database error and not created
+    let output = report("table");
+    assert_eq!(output.status.code(), Some(8));
+    assert!(!path.exists());
+}
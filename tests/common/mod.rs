@@ -0,0 +1,63 @@
+//! Fixtures shared between the integration tests.
+//!
+//! The fixtures are a hand curated snapshot of the `/trains` and `/stations`
+//! endpoints taken on the evening of 2023-08-29. Every integration test that
+//! does not exercise the HTTP client itself should build its data from here.
+#![allow(dead_code)]
+
+use amtrak_api::responses::{StationResponse, TrainResponse, TrainStatus};
+use chrono::{DateTime, Duration, FixedOffset};
+
+/// Raw JSON body of the `/trains` fixture
+pub const TRAINS_JSON: &str = include_str!("../fixtures/trains.json");
+
+/// Raw JSON body of the `/stations` fixture
+pub const STATIONS_JSON: &str = include_str!("../fixtures/stations.json");
+
+/// Returns the deserialized `/trains` fixture
+pub fn trains() -> TrainResponse {
+    serde_json::from_str(TRAINS_JSON).unwrap()
+}
+
+/// Returns the deserialized `/stations` fixture
+pub fn stations() -> StationResponse {
+    serde_json::from_str(STATIONS_JSON).unwrap()
+}
+
+/// The instant at which the fixtures were captured
+pub fn now() -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339("2023-08-29T22:30:00-04:00").unwrap()
+}
+
+/// Returns the `/trains` fixture as it would look three minutes later:
+///
+/// * Keystone 657 arrived at Paoli and is now 7 minutes late
+/// * Northeast Regional 94 departed Wilmington
+/// * Capitol Corridor 538 arrived at and departed Emeryville in between
+///
+/// Only the trains listed above report an update.
+pub fn later_trains() -> TrainResponse {
+    let mut response = trains();
+
+    for train in response.0.values_mut().flatten() {
+        let (code, status) = match train.train_id.as_str() {
+            "657-29" => {
+                train.train_timely = "7 Minutes Late".to_string();
+                ("PAO", TrainStatus::Station)
+            }
+            "94-29" => ("WIL", TrainStatus::Departed),
+            "538-29" => ("EMY", TrainStatus::Departed),
+            _ => continue,
+        };
+        train.updated_at += Duration::minutes(3);
+        train.last_value += Duration::minutes(3);
+
+        for station in &mut train.stations {
+            if station.code == code {
+                station.status = status;
+            }
+        }
+    }
+
+    response
+}
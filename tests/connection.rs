@@ -0,0 +1,120 @@
+mod common;
+
+use amtrak_api::connection::{Connection, ConnectionMonitor, ConnectionRisk};
+use chrono::Duration;
+
+#[test]
+fn test_evaluate() {
+    let trains = common::trains();
+
+    // Keystone 657 arrives at 22:00, Northeast Regional 94 leaves at 23:08
+    let status = Connection::new("657-29", "94-29", "PHL").evaluate(&trains);
+    assert_eq!(status.station_code, "PHL");
+    assert_eq!(
+        status.projected_arrival,
+        Some("2023-08-29T22:00:00-04:00".parse().unwrap())
+    );
+    assert_eq!(
+        status.projected_departure,
+        Some("2023-08-29T23:08:00-04:00".parse().unwrap())
+    );
+    assert_eq!(status.slack_minutes, Some(68));
+    assert_eq!(status.risk, ConnectionRisk::Safe);
+}
+
+#[test]
+fn test_evaluate_risk_levels() {
+    let trains = common::trains();
+    let connection = Connection::new("657-29", "94-29", "phl");
+
+    let risk = |min_connection, margin| {
+        connection
+            .clone()
+            .min_connection(Duration::minutes(min_connection))
+            .margin(Duration::minutes(margin))
+            .evaluate(&trains)
+            .risk
+    };
+    assert_eq!(risk(15, 10), ConnectionRisk::Safe);
+    assert_eq!(risk(60, 10), ConnectionRisk::Tight);
+    assert_eq!(risk(75, 10), ConnectionRisk::AtRisk);
+}
+
+#[test]
+fn test_evaluate_missed() {
+    let trains = common::trains();
+
+    // Keystone 657 already left Philadelphia before 94 gets there
+    let status = Connection::new("94-29", "657-29", "PHL").evaluate(&trains);
+    assert_eq!(status.slack_minutes, Some(-55));
+    assert_eq!(status.risk, ConnectionRisk::Missed);
+
+    // Keystone 657 is still enroute to Paoli while 612 already departed from
+    // it, whatever the estimates of 612 say
+    let mut trains = trains;
+    for train in trains.0.values_mut().flatten() {
+        if train.train_id == "612-29" {
+            for station in &mut train.stations {
+                station.departure = Some("2023-08-30T00:00:00-04:00".parse().unwrap());
+            }
+        }
+    }
+    let status = Connection::new("657-29", "612-29", "PAO").evaluate(&trains);
+    assert!(status.slack_minutes.unwrap() > 0);
+    assert_eq!(status.risk, ConnectionRisk::Missed);
+}
+
+#[test]
+fn test_evaluate_unknown() {
+    let trains = common::trains();
+
+    let status = Connection::new("657-29", "999-29", "PHL").evaluate(&trains);
+    assert_eq!(status.projected_departure, None);
+    assert_eq!(status.slack_minutes, None);
+    assert_eq!(status.risk, ConnectionRisk::Unknown);
+
+    let status = Connection::new("657-29", "538-29", "PHL").evaluate(&trains);
+    assert!(status.projected_arrival.is_some());
+    assert_eq!(status.risk, ConnectionRisk::Unknown);
+}
+
+#[test]
+fn test_monitor() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.watch(Connection::new("657-29", "94-29", "PHL"));
+    monitor.watch(Connection::new("94-29", "43-30", "PHL"));
+    assert_eq!(monitor.connections().count(), 2);
+
+    let changes = monitor.update(&common::trains());
+    assert_eq!(changes.len(), 2);
+    assert!(
+        changes
+            .iter()
+            .all(|change| change.previous_risk.is_none()
+                && change.status.risk == ConnectionRisk::Safe)
+    );
+
+    // Nothing changed
+    assert!(monitor.update(&common::later_trains()).is_empty());
+
+    // Northeast Regional 94 is now expected to leave Philadelphia at 22:10
+    let mut trains = common::later_trains();
+    for train in trains.0.values_mut().flatten() {
+        if train.train_id == "94-29" {
+            for station in &mut train.stations {
+                if station.code == "PHL" {
+                    station.departure = Some("2023-08-29T22:10:00-04:00".parse().unwrap());
+                }
+            }
+        }
+    }
+
+    let changes = monitor.update(&trains);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].previous_risk, Some(ConnectionRisk::Safe));
+    assert_eq!(changes[0].status.inbound_train_id, "657-29");
+    assert_eq!(changes[0].status.slack_minutes, Some(10));
+    assert_eq!(changes[0].status.risk, ConnectionRisk::AtRisk);
+
+    assert!(monitor.update(&trains).is_empty());
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "bundled-stations")]
+
+use amtrak_api::directory;
+
+fn codes(stations: Vec<&amtrak_api::responses::Station>) -> Vec<&str> {
+    stations
+        .into_iter()
+        .map(|station| station.code.as_str())
+        .collect()
+}
+
+#[test]
+fn test_stations() {
+    let stations = directory::stations();
+
+    assert!(!stations.0.is_empty());
+    assert!(stations.0.values().all(|station| station.trains.is_empty()));
+    assert!(stations
+        .0
+        .iter()
+        .all(|(code, station)| *code == station.code && !station.tz.is_empty()));
+    assert!(std::ptr::eq(stations, directory::stations()));
+}
+
+// The bundled snapshot is the whole network, so these tests only assert
+// facts that hold for the real stations rather than an exact list.
+
+#[test]
+fn test_station() {
+    let philadelphia = directory::station("PHL").unwrap();
+    assert!(philadelphia.name.starts_with("Philadelphia"));
+    assert_eq!(philadelphia.city, "Philadelphia");
+    assert_eq!(philadelphia.tz, "America/New_York");
+    assert_eq!(philadelphia.zip, "19104");
+    assert!((philadelphia.lat - 39.9557).abs() < 0.01);
+
+    assert_eq!(directory::station("phl").unwrap().code, "PHL");
+    assert!(directory::station("XYZ").is_none());
+}
+
+#[test]
+fn test_station_by_name() {
+    let new_york = directory::station("NYP").unwrap();
+    assert_eq!(
+        directory::station_by_name(&new_york.name.to_uppercase())
+            .unwrap()
+            .code,
+        "NYP"
+    );
+    assert!(directory::station_by_name("xyzzy").is_none());
+}
+
+#[test]
+fn test_search() {
+    let penn = directory::search("penn");
+    for code in ["BAL", "NYP", "NWK"] {
+        assert!(codes(penn.clone()).contains(&code), "penn misses {}", code);
+    }
+    assert!(penn.windows(2).all(|pair| pair[0].name <= pair[1].name));
+
+    assert!(codes(directory::search("balt pen")).contains(&"BAL"));
+    assert!(codes(directory::search("Philadelphia")).contains(&"PHL"));
+    assert!(codes(directory::search("30th")).contains(&"PHL"));
+    assert!(directory::search("").is_empty());
+    assert!(directory::search("xyzzy").is_empty());
+}
+
+#[test]
+fn test_fuzzy_search() {
+    let codes = |query: &str| -> Vec<&str> {
+        directory::fuzzy_search(query)
+            .into_iter()
+            .map(|found| found.station.code.as_str())
+            .collect()
+    };
+
+    let penn = codes("penn");
+    assert_eq!(penn[0], "NYP");
+    assert!(penn.contains(&"NWK") && penn.contains(&"BAL"));
+    assert_eq!(codes("balt pen")[0], "BAL");
+    assert_eq!(codes("philly")[0], "PHL");
+    assert_eq!(codes("philadelpia")[0], "PHL");
+    assert!(directory::fuzzy_search("").is_empty());
+}
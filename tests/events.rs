@@ -0,0 +1,95 @@
+mod common;
+
+use amtrak_api::events::{self, EventFilter, TrainEventKind};
+
+#[test]
+fn test_diff() {
+    let events = events::diff(&common::trains(), &common::later_trains());
+
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| {
+            (
+                event.train_id.as_str(),
+                event.station_code.as_str(),
+                &event.kind,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("538-29", "EMY", &TrainEventKind::Arrival),
+            ("538-29", "EMY", &TrainEventKind::Departure),
+            ("657-29", "PAO", &TrainEventKind::Arrival),
+            (
+                "657-29",
+                "PAO",
+                &TrainEventKind::DelayChange {
+                    previous_delay_minutes: Some(5),
+                    delay_minutes: Some(7),
+                }
+            ),
+            ("94-29", "WIL", &TrainEventKind::Departure),
+        ]
+    );
+
+    assert_eq!(events[0].route_name, "Capitol Corridor");
+    assert_eq!(events[0].train_num, 538);
+}
+
+#[test]
+fn test_diff_unchanged() {
+    assert!(events::diff(&common::trains(), &common::trains()).is_empty());
+}
+
+#[test]
+fn test_diff_new_train() {
+    let mut previous = common::trains();
+    previous.0.retain(|_, trains| {
+        trains.retain(|train| train.train_id != "657-29");
+        !trains.is_empty()
+    });
+
+    let events = events::diff(&previous, &common::later_trains());
+    assert!(events.iter().all(|event| event.train_id != "657-29"));
+    assert_eq!(events.len(), 3);
+}
+
+#[test]
+fn test_serialize() {
+    let events = events::diff(&common::trains(), &common::later_trains());
+    let value = serde_json::to_value(&events[3]).unwrap();
+
+    assert_eq!(value["type"], "delay_change");
+    assert_eq!(value["train_id"], "657-29");
+    assert_eq!(value["previous_delay_minutes"], 5);
+    assert_eq!(value["delay_minutes"], 7);
+}
+
+#[test]
+fn test_filter() {
+    let events = events::diff(&common::trains(), &common::later_trains());
+    let count = |filter: &EventFilter| events.iter().filter(|e| filter.matches(e)).count();
+
+    assert_eq!(count(&EventFilter::default()), 5);
+
+    let filter = EventFilter {
+        train_ids: vec!["94-29".to_string(), "538-29".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(count(&filter), 3);
+
+    let filter = EventFilter {
+        routes: vec!["keystone".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(count(&filter), 2);
+
+    let filter = EventFilter {
+        routes: vec!["Keystone".to_string(), "Northeast Regional".to_string()],
+        stations: vec!["wil".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(count(&filter), 1);
+}
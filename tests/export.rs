@@ -0,0 +1,91 @@
+mod common;
+
+use amtrak_api::{export::StopEvent, responses::TrainStatus};
+use chrono::{DateTime, Utc};
+
+fn utc(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[test]
+fn test_stop_events() {
+    let events = common::trains().stop_events();
+    assert_eq!(events.len(), 33);
+
+    // Rows are ordered by train id and then by stop sequence
+    let order: Vec<_> = events
+        .iter()
+        .map(|event| (event.train_id.as_str(), event.stop_sequence))
+        .collect();
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_eq!(order, sorted);
+
+    let event = events
+        .iter()
+        .find(|event| event.train_id == "538-29" && event.station_code == "OKJ")
+        .unwrap();
+    assert_eq!(
+        event,
+        &StopEvent {
+            train_id: "538-29".to_string(),
+            train_num: 538,
+            route_name: "Capitol Corridor".to_string(),
+            stop_sequence: 2,
+            station_code: "OKJ".to_string(),
+            scheduled_arrival: utc("2023-08-30T02:25:00Z"),
+            scheduled_departure: utc("2023-08-30T02:27:00Z"),
+            actual_arrival: Some(utc("2023-08-30T02:24:00Z")),
+            actual_departure: Some(utc("2023-08-30T02:27:00Z")),
+            arrival_delay_minutes: Some(-1),
+            departure_delay_minutes: Some(0),
+            status: TrainStatus::Departed,
+        }
+    );
+
+    // Predeparture trains do not have any predictions yet
+    let event = events
+        .iter()
+        .find(|event| event.train_id == "43-30")
+        .unwrap();
+    assert_eq!(event.actual_arrival, None);
+    assert_eq!(event.arrival_delay_minutes, None);
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_write_csv() -> Result<(), amtrak_api::errors::Error> {
+    let mut response = common::trains();
+    response.0.retain(|train_num, _| train_num == "538");
+
+    let mut output = Vec::new();
+    response.write_csv(&mut output)?;
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], StopEvent::COLUMNS.join(","));
+    assert_eq!(
+        lines[2],
+        "538-29,538,Capitol Corridor,2,OKJ,2023-08-30T02:25:00Z,2023-08-30T02:27:00Z,\
+         2023-08-30T02:24:00Z,2023-08-30T02:27:00Z,-1,0,Departed"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn test_write_csv_empty() -> Result<(), amtrak_api::errors::Error> {
+    let mut output = Vec::new();
+    amtrak_api::export::write_csv(Vec::new(), &mut output)?;
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!("{}\n", StopEvent::COLUMNS.join(","))
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use amtrak_api::{freshness::Freshness, responses::Train};
+use chrono::Duration;
+
+fn threshold() -> Duration {
+    Duration::minutes(Freshness::DEFAULT_THRESHOLD_MINUTES)
+}
+
+fn train(train_id: &str) -> Train {
+    common::trains()
+        .0
+        .into_values()
+        .flatten()
+        .find(|train| train.train_id == train_id)
+        .unwrap()
+}
+
+#[test]
+fn test_data_age() {
+    assert_eq!(
+        train("657-29").data_age(common::now()),
+        Duration::seconds(75)
+    );
+    assert_eq!(
+        train("612-29").data_age(common::now()),
+        Duration::minutes(266)
+    );
+    assert_eq!(
+        train("538-29").data_age(common::now().to_utc()),
+        Duration::seconds(30)
+    );
+}
+
+#[test]
+fn test_freshness() {
+    let now = common::now();
+
+    assert_eq!(
+        train("657-29").freshness(now, threshold()),
+        Freshness::Fresh
+    );
+    assert_eq!(
+        train("612-29").freshness(now, threshold()),
+        Freshness::Stale
+    );
+    assert_eq!(train("43-30").freshness(now, threshold()), Freshness::Stale);
+    assert_eq!(
+        train("612-29").freshness(now, Duration::hours(5)),
+        Freshness::Fresh
+    );
+
+    // The API refreshes the train but its position is stuck
+    let mut stuck = train("657-29");
+    stuck.updated_at = now + Duration::minutes(10);
+    assert_eq!(
+        stuck.freshness(now + Duration::minutes(20), threshold()),
+        Freshness::PositionStale
+    );
+
+    // A train created after its last position report is not stale yet
+    let mut created = train("43-30");
+    created.created_at = now - Duration::minutes(5);
+    assert_eq!(created.freshness(now, threshold()), Freshness::Fresh);
+}
+
+#[test]
+fn test_ghost_trains() {
+    let now = common::now();
+    let response = common::trains();
+
+    let ghosts: Vec<_> = response
+        .ghost_trains(now, threshold())
+        .map(|train| train.train_id.as_str())
+        .collect();
+    assert_eq!(ghosts, ["612-29"]);
+
+    // The Pennsylvanian is stale but has not departed yet
+    assert!(!train("43-30").is_ghost(now, threshold()));
+    assert!(train("612-29").is_ghost(now, threshold()));
+}
+
+#[test]
+fn test_remove_ghost_trains() {
+    let mut response = common::trains();
+
+    let removed = response.remove_ghost_trains(common::now(), threshold());
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].train_id, "612-29");
+    assert!(!response.0.contains_key("612"));
+    assert_eq!(response.0.values().flatten().count(), 4);
+
+    let removed = response.remove_ghost_trains(common::now(), threshold());
+    assert!(removed.is_empty());
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use amtrak_api::geo::{BoundingBox, Coordinate, Polygon, Region};
+
+fn northeast_corridor() -> BoundingBox {
+    BoundingBox::new(Coordinate::new(38.5, -77.5), Coordinate::new(42.5, -70.0))
+}
+
+fn bay_area() -> Polygon {
+    Polygon::new(vec![
+        Coordinate::new(37.2, -122.6),
+        Coordinate::new(37.2, -121.7),
+        Coordinate::new(38.1, -121.9),
+        Coordinate::new(38.1, -122.6),
+    ])
+}
+
+#[test]
+fn test_distance() {
+    let phl = Coordinate::new(39.9557, -75.1820);
+    let nyp = Coordinate::new(40.7506, -73.9935);
+
+    let distance = phl.distance_to(nyp);
+    assert!((distance - 83.0).abs() < 1.0, "distance was {}", distance);
+    assert_eq!(phl.distance_to(phl), 0.0);
+}
+
+#[test]
+fn test_bounding_box() {
+    let region = northeast_corridor();
+
+    assert!(region.contains(Coordinate::new(39.9557, -75.1820)));
+    assert!(region.contains(region.south_west));
+    assert!(region.contains(region.north_east));
+    assert!(!region.contains(Coordinate::new(37.8170, -122.2820)));
+    assert!(!region.contains(Coordinate::new(43.0, -75.0)));
+}
+
+#[test]
+fn test_polygon() {
+    let region = bay_area();
+
+    assert!(region.contains(Coordinate::new(37.8170, -122.2820)));
+    assert!(!region.contains(Coordinate::new(38.5840, -121.5007)));
+    assert!(!region.contains(Coordinate::new(39.9557, -75.1820)));
+
+    // The north east corner is cut diagonally
+    assert!(!region.contains(Coordinate::new(38.0, -121.8)));
+    assert!(!Polygon::new(vec![]).contains(Coordinate::new(0.0, 0.0)));
+}
+
+#[test]
+fn test_trains_within() {
+    let response = common::trains();
+    let northeast_corridor = northeast_corridor();
+    let bay_area = bay_area();
+
+    let mut northeast: Vec<_> = response
+        .trains_within(&northeast_corridor)
+        .map(|train| train.train_id.as_str())
+        .collect();
+    northeast.sort();
+    assert_eq!(northeast, vec!["43-30", "612-29", "657-29", "94-29"]);
+
+    let trains: Vec<_> = response
+        .trains_within(&bay_area)
+        .map(|train| train.train_id.as_str())
+        .collect();
+    assert_eq!(trains, vec!["538-29"]);
+}
+
+#[test]
+fn test_trains_passing_through() {
+    let response = common::trains();
+    let stations = common::stations();
+
+    // Only the Keystone (657) and the Pennsylvanian (43) will still stop at
+    // Harrisburg, the other Keystone (612) already departed from it
+    let harrisburg = BoundingBox::new(Coordinate::new(40.2, -77.0), Coordinate::new(40.3, -76.8));
+    let mut trains: Vec<_> = response
+        .trains_passing_through(&harrisburg, &stations)
+        .map(|train| train.train_id.as_str())
+        .collect();
+    trains.sort();
+    assert_eq!(trains, vec!["43-30", "657-29"]);
+
+    // The Capitol Corridor train is currently in the bay area and has yet to
+    // reach Sacramento
+    let sacramento = BoundingBox::new(Coordinate::new(38.5, -121.6), Coordinate::new(38.7, -121.4));
+    let trains: Vec<_> = response
+        .trains_passing_through(&sacramento, &stations)
+        .map(|train| train.train_id.as_str())
+        .collect();
+    assert_eq!(trains, vec!["538-29"]);
+}
+
+#[test]
+fn test_remaining_stations() {
+    let response = common::trains();
+    let train = &response.0["94"][0];
+
+    let remaining: Vec<_> = train
+        .remaining_stations()
+        .map(|station| station.code.as_str())
+        .collect();
+    assert_eq!(remaining, vec!["WIL", "PHL", "TRE", "NWK", "NYP"]);
+}
+
+#[test]
+fn test_bearing_and_destination() {
+    let phl = Coordinate::new(39.9557, -75.1820);
+    let nyp = Coordinate::new(40.7506, -73.9935);
+
+    let bearing = phl.bearing_to(nyp);
+    assert!((bearing - 48.0).abs() < 1.0, "bearing was {}", bearing);
+    assert!((nyp.bearing_to(phl) - 229.0).abs() < 1.0);
+    assert!(phl.destination(bearing, 0.0).distance_to(phl) < 1e-6);
+
+    let reached = phl.destination(bearing, phl.distance_to(nyp));
+    assert!(reached.distance_to(nyp) < 0.01, "reached {:?}", reached);
+
+    let north = phl.destination(0.0, 69.0);
+    assert!((north.lat - 40.955).abs() < 0.01);
+    assert!((north.lon - phl.lon).abs() < 1e-9);
+}
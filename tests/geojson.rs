@@ -0,0 +1,87 @@
+mod common;
+
+use serde_json::json;
+
+#[test]
+fn test_trains_geojson() {
+    let response = common::trains();
+    let geojson = response.to_geojson();
+
+    assert_eq!(geojson["type"], "FeatureCollection");
+
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(features.len(), 5);
+
+    let keystone = features
+        .iter()
+        .find(|feature| feature["id"] == "657-29")
+        .unwrap();
+    assert_eq!(keystone["type"], "Feature");
+    assert_eq!(
+        keystone["geometry"],
+        json!({ "type": "Point", "coordinates": [-75.3870, 40.0255] })
+    );
+    assert_eq!(
+        keystone["properties"],
+        json!({
+            "trainId": "657-29",
+            "trainNum": 657,
+            "routeName": "Keystone",
+            "originCode": "NYP",
+            "destinationCode": "HAR",
+            "delayMinutes": 5,
+            "heading": "W",
+            "state": "Active",
+            "velocity": 62.5,
+            "updatedAt": "2023-08-29T22:29:10-04:00",
+        })
+    );
+}
+
+#[test]
+fn test_stations_geojson() {
+    let stations = common::stations();
+    let geojson = stations.to_geojson();
+
+    let features = geojson["features"].as_array().unwrap();
+    assert_eq!(features.len(), stations.0.len());
+
+    let phl = features
+        .iter()
+        .find(|feature| feature["id"] == "PHL")
+        .unwrap();
+    assert_eq!(phl["geometry"]["coordinates"], json!([-75.1820, 39.9557]));
+    assert_eq!(phl["properties"]["name"], "Philadelphia 30th Street");
+    assert_eq!(phl["properties"]["state"], "PA");
+}
+
+#[test]
+fn test_route_geojson() {
+    let response = common::trains();
+    let stations = common::stations();
+
+    let train = &response.0["538"][0];
+    let route = train.route_to_geojson(&stations).unwrap();
+    assert_eq!(route["geometry"]["type"], "LineString");
+    assert_eq!(
+        route["geometry"]["coordinates"],
+        json!([
+            [-121.9027, 37.3297],
+            [-122.2716, 37.7940],
+            [-122.2918, 37.8405],
+            [-122.3534, 37.9369],
+            [-121.5007, 38.5840],
+        ])
+    );
+    assert_eq!(
+        route["properties"]["stops"],
+        json!(["SJC", "OKJ", "EMY", "RIC", "SAC"])
+    );
+
+    // Without any known station there is nothing to draw
+    let empty = amtrak_api::responses::StationResponse(Default::default());
+    assert!(train.route_to_geojson(&empty).is_none());
+
+    let routes = response.routes_to_geojson(&stations);
+    assert_eq!(routes["features"].as_array().unwrap().len(), 5);
+}
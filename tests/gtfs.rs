@@ -0,0 +1,190 @@
+#![cfg(feature = "gtfs")]
+
+mod common;
+
+use amtrak_api::gtfs::GtfsFeed;
+use chrono::Duration;
+use std::{fs, io::Write, path::PathBuf};
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gtfs")
+}
+
+#[test]
+fn test_load_directory() -> Result<(), amtrak_api::errors::Error> {
+    let feed = GtfsFeed::open(fixture_dir())?;
+
+    let route = feed.route("88").unwrap();
+    assert_eq!(route.route_long_name.as_deref(), Some("Keystone"));
+    assert_eq!(route.route_color.as_deref(), Some("1F5BA8"));
+    assert_eq!(feed.route("35").unwrap().route_color, None);
+
+    assert_eq!(feed.stop("PHL").unwrap().stop_lat, 39.9557);
+    assert_eq!(feed.stop_times("94_DAILY").len(), 7);
+    assert_eq!(
+        feed.stop_times("94_DAILY")[6].arrival_time.as_deref(),
+        Some("24:15:00")
+    );
+    assert_eq!(feed.shape("KEYS_W").len(), 8);
+    assert!(feed.shape("missing").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_load_zip() -> Result<(), amtrak_api::errors::Error> {
+    let path = std::env::temp_dir().join(format!("amtrak-api-gtfs-{}.zip", std::process::id()));
+
+    let mut writer = zip::ZipWriter::new(fs::File::create(&path)?);
+    for entry in fs::read_dir(fixture_dir())? {
+        let entry = entry?;
+        writer.start_file(
+            entry.file_name().to_string_lossy(),
+            zip::write::SimpleFileOptions::default(),
+        )?;
+        writer.write_all(&fs::read(entry.path())?)?;
+    }
+    writer.finish()?;
+
+    let feed = GtfsFeed::open(&path);
+    fs::remove_file(&path)?;
+    let feed = feed?;
+
+    assert_eq!(feed.trip("657_WKDY").unwrap().route_id, "88");
+    assert_eq!(feed.stop_times("657_WKND").len(), 7);
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_feed() {
+    assert!(matches!(
+        GtfsFeed::from_dir(fixture_dir().join("missing")),
+        Err(amtrak_api::errors::Error::IoFailed(_))
+    ));
+}
+
+#[test]
+fn test_match_train() -> Result<(), amtrak_api::errors::Error> {
+    let feed = GtfsFeed::open(fixture_dir())?;
+    let response = common::trains();
+
+    // 2023-08-29 is a Tuesday so the weekday trip is picked
+    let train = &response.0["657"][0];
+    let matched = feed.match_train(train).unwrap();
+    assert_eq!(matched.trip.trip_id, "657_WKDY");
+    assert_eq!(matched.route_color(), Some("1F5BA8"));
+    assert_eq!(matched.shape.len(), 8);
+    assert_eq!(matched.stop_times.len(), train.stations.len());
+    assert_eq!(matched.stop_sequence("PHL"), Some(4));
+    assert_eq!(matched.stop_sequence("WAS"), None);
+
+    // The Capitol Corridor is not part of the fixture feed
+    assert!(feed.match_train(&response.0["538"][0]).is_none());
+
+    let mut matched: Vec<_> = feed
+        .match_response(&response)
+        .map(|(train, matched)| (train.train_id.as_str(), matched.trip.trip_id.as_str()))
+        .collect();
+    matched.sort();
+    assert_eq!(
+        matched,
+        vec![
+            ("43-30", "43_DAILY"),
+            ("657-29", "657_WKDY"),
+            ("94-29", "94_DAILY"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_match_train_calendar_exception() -> Result<(), amtrak_api::errors::Error> {
+    let feed = GtfsFeed::open(fixture_dir())?;
+
+    // Labor Day (2023-09-04) runs on the weekend schedule
+    let mut train = common::trains().0["657"][0].clone();
+    for station in &mut train.stations {
+        station.schedule_departure += Duration::days(6);
+    }
+
+    let matched = feed.match_train(&train).unwrap();
+    assert_eq!(matched.trip.trip_id, "657_WKND");
+    assert_eq!(matched.stop_sequence("ARD"), None);
+    assert_eq!(matched.stop_sequence("PAO"), Some(5));
+
+    Ok(())
+}
+
+#[cfg(feature = "gtfs-rt")]
+#[test]
+fn test_feed_options() -> Result<(), amtrak_api::errors::Error> {
+    let feed = std::sync::Arc::new(GtfsFeed::open(fixture_dir())?);
+    let message = feed
+        .feed_options()
+        .feed_message(&common::trains(), common::now());
+
+    let trips: Vec<_> = message
+        .entity
+        .iter()
+        .filter_map(|entity| entity.trip_update.as_ref())
+        .map(|update| {
+            (
+                update.trip.trip_id.as_deref().unwrap(),
+                update.trip.route_id.as_deref().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        trips,
+        vec![
+            ("43_DAILY", "35"),
+            ("538-29", "Capitol Corridor"),
+            ("612-29", "Keystone"),
+            ("657_WKDY", "88"),
+            ("94_DAILY", "50"),
+        ]
+    );
+
+    let trip_update = |trip_id: &str| {
+        message
+            .entity
+            .iter()
+            .filter_map(|entity| entity.trip_update.as_ref())
+            .find(|update| update.trip.trip_id.as_deref() == Some(trip_id))
+            .unwrap()
+    };
+    let philadelphia = trip_update("657_WKDY")
+        .stop_time_update
+        .iter()
+        .find(|update| update.stop_id.as_deref() == Some("PHL"))
+        .unwrap();
+    assert_eq!(philadelphia.stop_sequence, Some(4));
+
+    // Trains without a matching trip do not get a stop sequence
+    assert!(trip_update("538-29")
+        .stop_time_update
+        .iter()
+        .all(|update| update.stop_sequence.is_none()));
+
+    Ok(())
+}
+
+#[test]
+fn test_route_shapes() -> Result<(), amtrak_api::errors::Error> {
+    let feed = GtfsFeed::open(fixture_dir())?;
+    let shapes = feed.route_shapes();
+
+    assert_eq!(shapes.len(), 1);
+    let keystone = shapes.get("Keystone").unwrap();
+    assert_eq!(keystone.points().len(), 8);
+    assert!(
+        (keystone.length() - 179.5).abs() < 1.0,
+        "{}",
+        keystone.length()
+    );
+    assert!(shapes.get("Northeast Regional").is_none());
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "gtfs-rt")]
+
+mod common;
+
+use amtrak_api::gtfs_rt::{vehicle_position::VehicleStopStatus, FeedMessage, FeedOptions};
+use prost::Message;
+use std::{env, fs, path::Path};
+
+/// Compares `actual` with the content of the golden file. Setting the
+/// `UPDATE_GOLDEN` environment variable rewrites the golden file instead.
+fn assert_golden(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+    }
+
+    let expected = fs::read_to_string(&path).unwrap();
+    assert_eq!(expected, actual, "golden file {} does not match", name);
+}
+
+#[test]
+fn test_feed_message_golden() {
+    // Only keep the active Keystone to keep the golden file reviewable
+    let mut response = common::trains();
+    response.0.retain(|train_num, _| train_num == "657");
+    let feed = response.to_gtfs_realtime(common::now());
+
+    assert_golden("gtfs_rt_keystone_657.txt", &format!("{:#?}\n", feed));
+}
+
+#[test]
+fn test_feed_message_round_trip() {
+    let feed = common::trains().to_gtfs_realtime(common::now());
+    let decoded = FeedMessage::decode(feed.encode_to_vec().as_slice()).unwrap();
+
+    assert_eq!(feed, decoded);
+    assert_eq!(decoded.header.gtfs_realtime_version, "2.0");
+    assert_eq!(decoded.entity.len(), 10);
+}
+
+#[test]
+fn test_vehicle_position() {
+    let feed = common::trains().to_gtfs_realtime(common::now());
+
+    let vehicle = feed
+        .entity
+        .iter()
+        .find(|entity| entity.id == "vehicle-94-29")
+        .and_then(|entity| entity.vehicle.as_ref())
+        .unwrap();
+
+    assert_eq!(vehicle.stop_id.as_deref(), Some("WIL"));
+    assert_eq!(vehicle.current_stop_sequence, None);
+    assert_eq!(
+        vehicle.current_status,
+        Some(VehicleStopStatus::StoppedAt as i32)
+    );
+
+    let position = vehicle.position.as_ref().unwrap();
+    assert_eq!(position.latitude, 39.7369);
+    assert_eq!(position.bearing, Some(45.0));
+}
+
+#[test]
+fn test_custom_ids() {
+    let options = FeedOptions::new()
+        .with_trip_id(|train| format!("trip-{}", train.train_num))
+        .with_route_id(|train| train.route_name.to_uppercase())
+        .with_stop_id(|station| format!("AMTK-{}", station.code));
+
+    let feed = options.feed_message(&common::trains(), common::now());
+    let trip_update = feed
+        .entity
+        .iter()
+        .find_map(|entity| entity.trip_update.as_ref())
+        .unwrap();
+
+    assert_eq!(trip_update.trip.trip_id.as_deref(), Some("trip-43"));
+    assert_eq!(trip_update.trip.route_id.as_deref(), Some("PENNSYLVANIAN"));
+    assert_eq!(trip_update.trip.start_date.as_deref(), Some("20230830"));
+    assert_eq!(
+        trip_update.stop_time_update[0].stop_id.as_deref(),
+        Some("AMTK-NYP")
+    );
+}
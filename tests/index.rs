@@ -0,0 +1,95 @@
+mod common;
+
+use amtrak_api::responses::TrainResponse;
+
+fn ids<'a>(trains: impl IntoIterator<Item = &'a &'a amtrak_api::responses::Train>) -> Vec<&'a str> {
+    trains
+        .into_iter()
+        .map(|train| train.train_id.as_str())
+        .collect()
+}
+
+#[test]
+fn test_iter_trains() {
+    let response = common::trains();
+    assert_eq!(response.iter_trains().count(), 5);
+    assert_eq!(response.len(), 5);
+    assert!(!response.is_empty());
+
+    let empty: TrainResponse = serde_json::from_str("[]").unwrap();
+    assert_eq!(empty.len(), 0);
+    assert!(empty.is_empty());
+    assert_eq!(empty.iter_trains().count(), 0);
+}
+
+#[test]
+fn test_into_iter() {
+    let response = common::trains();
+
+    let mut borrowed: Vec<_> = (&response)
+        .into_iter()
+        .map(|train| train.train_id.clone())
+        .collect();
+    borrowed.sort();
+
+    let mut owned: Vec<_> = response.into_iter().map(|train| train.train_id).collect();
+    owned.sort();
+
+    assert_eq!(borrowed, owned);
+    assert_eq!(owned, ["43-30", "538-29", "612-29", "657-29", "94-29"]);
+}
+
+#[test]
+fn test_get_by_id() {
+    let response = common::trains();
+
+    assert_eq!(response.get_by_id("657-29").unwrap().train_num, 657);
+    assert!(response.get_by_id("657-30").is_none());
+}
+
+#[test]
+fn test_by_route() {
+    let response = common::trains();
+    let routes = response.by_route();
+
+    assert_eq!(
+        routes.keys().copied().collect::<Vec<_>>(),
+        [
+            "Capitol Corridor",
+            "Keystone",
+            "Northeast Regional",
+            "Pennsylvanian"
+        ]
+    );
+    assert_eq!(ids(&routes["Keystone"]), ["612-29", "657-29"]);
+}
+
+#[test]
+fn test_by_station() {
+    let response = common::trains();
+    let stations = response.by_station();
+
+    assert_eq!(
+        ids(&stations["PHL"]),
+        ["43-30", "612-29", "657-29", "94-29"]
+    );
+    assert_eq!(ids(&stations["SAC"]), ["538-29"]);
+    assert!(!stations.contains_key("PGX"));
+}
+
+#[test]
+fn test_index() {
+    let response = common::trains();
+    let index = response.index();
+
+    assert_eq!(index.len(), 5);
+    assert!(!index.is_empty());
+    assert_eq!(index.get("94-29").unwrap().route_name, "Northeast Regional");
+    assert!(index.get("94-30").is_none());
+    assert_eq!(ids(index.route("Keystone")), ["612-29", "657-29"]);
+    assert_eq!(ids(index.route("KEYSTONE")), ["612-29", "657-29"]);
+    assert!(index.route("Acela").is_empty());
+    assert_eq!(ids(index.station("PAO")), ["612-29", "657-29"]);
+    assert_eq!(ids(index.station("pao")), ["612-29", "657-29"]);
+    assert!(index.station("PGX").is_empty());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use amtrak_api::{
+    geo::Coordinate,
+    interpolate::PositionSource,
+    responses::{StationResponse, Train},
+};
+use chrono::Duration;
+
+fn train(train_id: &str) -> Train {
+    common::trains()
+        .0
+        .into_values()
+        .flatten()
+        .find(|train| train.train_id == train_id)
+        .unwrap()
+}
+
+fn station(code: &str) -> Coordinate {
+    common::stations().0[code].coordinate()
+}
+
+#[test]
+fn test_before_last_report() {
+    let keystone = train("657-29");
+    let position = keystone.position_at(
+        keystone.last_value - Duration::minutes(1),
+        &common::stations(),
+    );
+
+    assert_eq!(position.source, PositionSource::Reported);
+    assert_eq!(position.coordinate, keystone.coordinate());
+}
+
+#[test]
+fn test_along_path() {
+    let keystone = train("657-29");
+    let stations = common::stations();
+    let paoli = station("PAO");
+
+    // 62.5 mph for two minutes
+    let position = keystone.position_at(keystone.last_value + Duration::minutes(2), &stations);
+    assert_eq!(position.source, PositionSource::Path);
+
+    let travelled = keystone.coordinate().distance_to(position.coordinate);
+    assert!((travelled - 2.083).abs() < 0.01, "travelled {}", travelled);
+    let remaining = position.coordinate.distance_to(paoli);
+    assert!(
+        (keystone.coordinate().distance_to(paoli) - travelled - remaining).abs() < 0.01,
+        "left the path towards Paoli"
+    );
+
+    // Never overshoots the next station
+    let position = keystone.position_at(keystone.last_value + Duration::minutes(30), &stations);
+    assert_eq!(position.source, PositionSource::Path);
+    assert_eq!(position.coordinate, paoli);
+}
+
+#[test]
+fn test_clamped_at_station() {
+    let ghost = train("612-29");
+    let position = ghost.position_at(common::now(), &common::stations());
+
+    assert_eq!(position.source, PositionSource::Station);
+    assert_eq!(position.coordinate, station("PHL"));
+}
+
+#[test]
+fn test_dead_reckoning() {
+    let keystone = train("657-29");
+    let at = keystone.last_value + Duration::minutes(2);
+    let position = keystone.position_at(at, &StationResponse(Default::default()));
+
+    assert_eq!(position.source, PositionSource::DeadReckoning);
+    assert_eq!(position.coordinate, keystone.dead_reckon(at));
+
+    // Heading west
+    assert!((position.coordinate.lat - keystone.lat).abs() < 1e-3);
+    assert!(position.coordinate.lon < keystone.lon);
+    let travelled = keystone.coordinate().distance_to(position.coordinate);
+    assert!((travelled - 2.083).abs() < 0.01, "travelled {}", travelled);
+}
+
+#[test]
+fn test_stopped_train() {
+    let pennsylvanian = train("43-30");
+    let at = pennsylvanian.last_value + Duration::minutes(5);
+
+    assert_eq!(pennsylvanian.dead_reckon(at), pennsylvanian.coordinate());
+    assert_eq!(
+        pennsylvanian.position_at(at, &common::stations()).source,
+        PositionSource::Reported
+    );
+}
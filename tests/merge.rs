@@ -0,0 +1,131 @@
+mod common;
+
+use amtrak_api::responses::{TrainResponse, TrainStatus};
+use chrono::Duration;
+
+/// Returns a response only containing the train with the provided id
+fn only(mut response: TrainResponse, train_id: &str) -> TrainResponse {
+    response.0.retain(|_, trains| {
+        trains.retain(|train| train.train_id == train_id);
+        !trains.is_empty()
+    });
+    response
+}
+
+#[test]
+fn test_merge_keeps_newest() {
+    let mut response = common::trains();
+    let conflicts = response.merge(common::later_trains());
+
+    assert!(conflicts.is_empty());
+    assert_eq!(response.len(), 5);
+    assert_eq!(
+        response.get_by_id("657-29").unwrap().train_timely,
+        "7 Minutes Late"
+    );
+
+    // Older versions never replace newer ones
+    let mut response = common::later_trains();
+    assert!(response.merge(common::trains()).is_empty());
+    assert_eq!(response.len(), 5);
+    assert_eq!(
+        response.get_by_id("657-29").unwrap().updated_at,
+        common::trains().0["657"][0].updated_at + Duration::minutes(3)
+    );
+}
+
+#[test]
+fn test_merge_disjoint() {
+    let mut response = only(common::trains(), "657-29");
+    assert!(response.merge(only(common::trains(), "94-29")).is_empty());
+
+    assert_eq!(response.len(), 2);
+    assert!(response.get_by_id("94-29").is_some());
+}
+
+#[test]
+fn test_merge_conflicts() {
+    let mut regressed = only(common::later_trains(), "657-29");
+    for train in regressed.0.values_mut().flatten() {
+        train.updated_at += Duration::minutes(2);
+        for station in &mut train.stations {
+            if station.code == "ARD" || station.code == "PAO" {
+                station.status = TrainStatus::Enroute;
+            }
+        }
+    }
+
+    let mut response = common::later_trains();
+    let conflicts = response.merge(regressed);
+
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].train_id, "657-29");
+    assert_eq!(conflicts[0].station_code, "ARD");
+    assert_eq!(conflicts[0].kept_status, TrainStatus::Enroute);
+    assert_eq!(conflicts[0].discarded_status, TrainStatus::Departed);
+    assert!(conflicts[0].kept_updated_at > conflicts[0].discarded_updated_at);
+    assert_eq!(conflicts[1].station_code, "PAO");
+    assert_eq!(conflicts[1].discarded_status, TrainStatus::Station);
+
+    // The newest version is kept even though it contradicts the other one
+    let paoli = &response.get_by_id("657-29").unwrap().stations[5];
+    assert_eq!(paoli.status, TrainStatus::Enroute);
+}
+
+#[test]
+fn test_merge_same_update_disagreeing() {
+    let mut disagreeing = only(common::trains(), "657-29");
+    for train in disagreeing.0.values_mut().flatten() {
+        train.stations[5].status = TrainStatus::Station;
+    }
+
+    let mut response = common::trains();
+    let conflicts = response.merge(disagreeing);
+
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].station_code, "PAO");
+    assert_eq!(conflicts[0].kept_status, TrainStatus::Enroute);
+    assert_eq!(conflicts[0].discarded_status, TrainStatus::Station);
+    assert_eq!(
+        conflicts[0].kept_updated_at,
+        conflicts[0].discarded_updated_at
+    );
+
+    // The version already in the response wins ties
+    assert_eq!(
+        response.get_by_id("657-29").unwrap().stations[5].status,
+        TrainStatus::Enroute
+    );
+}
+
+#[test]
+fn test_merge_all() {
+    let (merged, conflicts) = TrainResponse::merge_all([
+        only(common::trains(), "657-29"),
+        common::later_trains(),
+        only(common::trains(), "612-29"),
+    ]);
+
+    assert!(conflicts.is_empty());
+    assert_eq!(merged.len(), 5);
+    assert_eq!(
+        merged.get_by_id("94-29").unwrap().stations[2].status,
+        TrainStatus::Departed
+    );
+}
+
+#[test]
+fn test_merge_moves_train_to_new_key() {
+    // The newer version of the Keystone is listed under another key
+    let mut later = only(common::later_trains(), "657-29");
+    let trains = later.0.remove("657").unwrap();
+    later.0.insert("657-29".to_string(), trains);
+
+    let mut response = common::trains();
+    assert!(response.merge(later).is_empty());
+
+    assert_eq!(response.len(), 5);
+    assert!(!response.0.contains_key("657"));
+    assert_eq!(response.0["657-29"][0].train_timely, "7 Minutes Late");
+    assert_eq!(response.0["612"].len(), 1);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use amtrak_api::{metrics::ClientMetrics, Client};
+
+#[test]
+fn test_network_metrics() {
+    let metrics = common::trains().network_metrics(common::now());
+
+    assert_eq!(
+        metrics.active_trains_per_route.get("Keystone").copied(),
+        Some(2)
+    );
+    assert_eq!(metrics.active_trains_per_route.get("Pennsylvanian"), None);
+    assert_eq!(metrics.average_delay_per_route["Keystone"], 4.0);
+    assert_eq!(metrics.average_delay_per_route["Northeast Regional"], 20.0);
+
+    assert_eq!(metrics.trains_per_state["Active"], 4);
+    assert_eq!(metrics.trains_per_state["Predeparture"], 1);
+    assert_eq!(metrics.trains_per_state["Completed"], 0);
+
+    assert_eq!(metrics.data_age_seconds["657-29"], 75);
+    assert_eq!(metrics.data_age_seconds["538-29"], 30);
+    assert_eq!(metrics.data_age_seconds["612-29"], 15960);
+}
+
+#[test]
+fn test_network_metrics_text() {
+    let text = common::trains().network_metrics(common::now()).to_string();
+
+    assert!(text.contains("# TYPE amtrak_active_trains gauge\n"));
+    assert!(text.contains("amtrak_active_trains{route=\"Capitol Corridor\"} 1\n"));
+    assert!(text.contains("amtrak_average_delay_minutes{route=\"Keystone\"} 4\n"));
+    assert!(text.contains("amtrak_trains{state=\"Predeparture\"} 1\n"));
+    assert!(text.contains("amtrak_train_data_age_seconds{train_id=\"94-29\"} 60\n"));
+    assert!(text.contains("amtrak_data_age_seconds_max 15960\n"));
+}
+
+#[tokio::test]
+async fn test_client_metrics() {
+    let mut server = mockito::Server::new_async().await;
+    let trains_mock = server
+        .mock("GET", "/trains")
+        .with_body(common::TRAINS_JSON)
+        .create_async()
+        .await;
+    let stations_mock = server
+        .mock("GET", "/stations")
+        .with_body("{")
+        .create_async()
+        .await;
+
+    let client = Client::with_base_url(&server.url());
+    client.clone().trains().await.unwrap();
+    assert!(client.stations().await.is_err());
+
+    let metrics = client.metrics();
+    assert_eq!(metrics.requests("trains"), 1);
+    assert_eq!(metrics.requests("stations"), 1);
+    assert_eq!(metrics.requests("station"), 0);
+    assert_eq!(metrics.errors("DeserializeFailed"), 1);
+    assert_eq!(metrics.errors("RequestFailed"), 0);
+
+    let text = metrics.to_string();
+    assert!(text.contains(
+        "amtrak_client_request_duration_seconds_bucket{endpoint=\"trains\",le=\"+Inf\"} 1\n"
+    ));
+    assert!(
+        text.contains("amtrak_client_request_duration_seconds_count{endpoint=\"stations\"} 1\n")
+    );
+    assert!(text.contains("amtrak_client_errors_total{variant=\"DeserializeFailed\"} 1\n"));
+
+    trains_mock.assert_async().await;
+    stations_mock.assert_async().await;
+}
+
+#[test]
+fn test_cache_metrics() {
+    let metrics = ClientMetrics::new();
+    metrics.record_cache_hit();
+    metrics.record_cache_hit();
+    metrics.record_cache_miss();
+
+    assert_eq!(metrics.cache_hits(), 2);
+    assert_eq!(metrics.cache_misses(), 1);
+
+    let text = metrics.to_string();
+    assert!(text.contains("amtrak_cache_hits_total 2\n"));
+    assert!(text.contains("amtrak_cache_misses_total 1\n"));
+}
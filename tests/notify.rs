@@ -0,0 +1,273 @@
+#![cfg(feature = "notify")]
+
+mod common;
+
+use amtrak_api::{
+    errors::Error,
+    notify::{Config, Notifier, Rule, Trigger, FORGET_AFTER_SNAPSHOTS},
+    responses::TrainResponse,
+    Client,
+};
+use mockito::Matcher;
+use serde_json::json;
+
+fn notifier(rules: &str) -> Notifier {
+    let document = format!("webhook = \"http://localhost/hook\"\n{}", rules);
+    Notifier::new(Config::from_toml(&document).unwrap())
+}
+
+fn messages(notifier: &mut Notifier, snapshot: &TrainResponse) -> Vec<String> {
+    notifier
+        .evaluate(snapshot)
+        .into_iter()
+        .map(|alert| alert.message)
+        .collect()
+}
+
+#[test]
+fn test_config() {
+    let config = Config::from_toml(
+        r#"
+webhook = "https://example.com/amtrak"
+
+[[rules]]
+name = "612 late at Philadelphia"
+train_num = 612
+station = "PHL"
+late_minutes = 15
+
+[[rules]]
+name = "Keystone leaving New York"
+route = "Keystone"
+station = "NYP"
+on = "departure"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.webhook.as_str(), "https://example.com/amtrak");
+    assert_eq!(
+        config.rules[0],
+        Rule {
+            name: "612 late at Philadelphia".to_string(),
+            train_num: Some(612),
+            route: None,
+            station: Some("PHL".to_string()),
+            on: None,
+            late_minutes: Some(15),
+        }
+    );
+    assert_eq!(config.rules[1].on, Some(Trigger::Departure));
+
+    let error =
+        Config::from_toml("webhook = \"https://example.com\"\n[[rules]]\nname = \"Nothing\"\n")
+            .unwrap_err();
+    assert!(matches!(error, Error::InvalidConfig(_)));
+    assert!(error
+        .to_string()
+        .starts_with("Invalid configuration: rule \"Nothing\" sets neither"));
+
+    let error = Config::from_toml("webhook = \"not a url\"\n").unwrap_err();
+    assert!(matches!(error, Error::ConfigFailed(_)));
+    assert!(error.to_string().contains("invalid URL \"not a url\""));
+
+    let error =
+        Config::from_toml("[[rules]]\nname = \"No webhook\"\nlate_minutes = 5\n").unwrap_err();
+    assert!(error.to_string().contains("webhook"));
+}
+
+#[test]
+fn test_late_rules() {
+    let mut notifier = notifier(
+        r#"
+[[rules]]
+name = "94 late at PHL"
+train_num = 94
+station = "phl"
+late_minutes = 15
+
+[[rules]]
+name = "612 late at PHL"
+train_num = 612
+station = "PHL"
+late_minutes = 15
+
+[[rules]]
+name = "Keystone late"
+route = "keystone"
+late_minutes = 5
+"#,
+    );
+
+    let alerts = notifier.evaluate(&common::trains());
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].rule, "94 late at PHL");
+    assert_eq!(alerts[0].station_code.as_deref(), Some("PHL"));
+    assert_eq!(alerts[0].delay_minutes, Some(20));
+    assert_eq!(
+        alerts[0].message,
+        "Northeast Regional 94 (94-29) is 20 minutes late at PHL"
+    );
+    assert_eq!(alerts[1].message, "Keystone 657 (657-29) is 5 minutes late");
+    assert_eq!(alerts[1].station_code, None);
+
+    // Already alerted
+    assert!(notifier.evaluate(&common::later_trains()).is_empty());
+}
+
+#[test]
+fn test_event_rules() {
+    let mut notifier = notifier(
+        r#"
+[[rules]]
+name = "Departs Wilmington"
+station = "WIL"
+on = "departure"
+
+[[rules]]
+name = "Keystone arrivals"
+route = "Keystone"
+on = "arrival"
+
+[[rules]]
+name = "Late departures"
+on = "departure"
+late_minutes = 30
+"#,
+    );
+
+    // Arrivals and departures need a previous snapshot
+    assert!(notifier.evaluate(&common::trains()).is_empty());
+
+    assert_eq!(
+        messages(&mut notifier, &common::later_trains()),
+        [
+            "Northeast Regional 94 (94-29) departed WIL",
+            "Keystone 657 (657-29) arrived at PAO",
+        ]
+    );
+    assert!(notifier.evaluate(&common::later_trains()).is_empty());
+}
+
+#[test]
+fn test_deduplication_forgets_gone_trains() {
+    let mut notifier = notifier(
+        r#"
+[[rules]]
+name = "Late"
+train_num = 94
+late_minutes = 15
+"#,
+    );
+
+    assert_eq!(notifier.evaluate(&common::trains()).len(), 1);
+    assert!(notifier.evaluate(&common::trains()).is_empty());
+
+    let mut without_94 = common::trains();
+    without_94.0.remove("94");
+
+    // A train briefly missing from the snapshots does not alert again
+    assert!(notifier.evaluate(&without_94).is_empty());
+    assert!(notifier.evaluate(&common::trains()).is_empty());
+
+    for _ in 0..FORGET_AFTER_SNAPSHOTS {
+        assert!(notifier.evaluate(&without_94).is_empty());
+    }
+    assert_eq!(notifier.evaluate(&common::trains()).len(), 1);
+}
+
+#[tokio::test]
+async fn test_poll_delivers_to_webhook() {
+    let mut server = mockito::Server::new_async().await;
+    let _trains_mock = server
+        .mock("GET", "/trains")
+        .with_body(common::TRAINS_JSON)
+        .create_async()
+        .await;
+    let failing_mock = server
+        .mock("POST", "/hook")
+        .with_status(500)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = Config::from_toml(&format!(
+        "webhook = \"{}/hook\"\n[[rules]]\nname = \"94 late\"\ntrain_num = 94\nlate_minutes = 15\n",
+        server.url()
+    ))
+    .unwrap();
+    let mut notifier = Notifier::new(config);
+    let client = Client::with_base_url(&server.url());
+
+    assert!(notifier.poll(&client).await.unwrap().is_empty());
+    assert_eq!(notifier.pending().len(), 1);
+    assert!(notifier.last_error().is_some());
+    failing_mock.assert_async().await;
+    failing_mock.remove_async().await;
+
+    // The alert that failed is delivered by the next poll
+    let hook_mock = server
+        .mock("POST", "/hook")
+        .match_header("content-type", "application/json")
+        .match_body(Matcher::PartialJson(json!({
+            "rule": "94 late",
+            "train_id": "94-29",
+            "train_num": 94,
+            "station_code": null,
+            "delay_minutes": 20,
+        })))
+        .expect(1)
+        .create_async()
+        .await;
+
+    let delivered = notifier.poll(&client).await.unwrap();
+    assert_eq!(delivered.len(), 1);
+    assert!(notifier.pending().is_empty());
+    assert!(notifier.last_error().is_none());
+    assert!(notifier.poll(&client).await.unwrap().is_empty());
+
+    hook_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_poll_drops_pending_alerts_of_forgotten_trains() {
+    let mut server = mockito::Server::new_async().await;
+    let trains_mock = server
+        .mock("GET", "/trains")
+        .with_body(common::TRAINS_JSON)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/hook")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let config = Config::from_toml(&format!(
+        "webhook = \"{}/hook\"\n[[rules]]\nname = \"94 late\"\ntrain_num = 94\nlate_minutes = 15\n",
+        server.url()
+    ))
+    .unwrap();
+    let mut notifier = Notifier::new(config);
+    let client = Client::with_base_url(&server.url());
+
+    assert!(notifier.poll(&client).await.unwrap().is_empty());
+    assert_eq!(notifier.pending().len(), 1);
+    trains_mock.remove_async().await;
+
+    let mut without_94 = common::trains();
+    without_94.0.remove("94");
+    server
+        .mock("GET", "/trains")
+        .with_body(serde_json::to_string(&without_94).unwrap())
+        .create_async()
+        .await;
+
+    for _ in 1..FORGET_AFTER_SNAPSHOTS {
+        notifier.poll(&client).await.unwrap();
+        assert_eq!(notifier.pending().len(), 1);
+    }
+    notifier.poll(&client).await.unwrap();
+    assert!(notifier.pending().is_empty());
+    assert!(notifier.last_error().is_none());
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use amtrak_api::planner::{Itinerary, TripPlanner};
+use chrono::Duration;
+
+fn train_ids(itineraries: &[Itinerary]) -> Vec<Vec<&str>> {
+    itineraries
+        .iter()
+        .map(|itinerary| {
+            itinerary
+                .legs()
+                .iter()
+                .map(|leg| leg.train_id.as_str())
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_direct() {
+    let trains = common::trains();
+
+    let itineraries = trains.trips("PHL", "HAR");
+    assert_eq!(train_ids(&itineraries), [["43-30"]]);
+
+    let leg = &itineraries[0].legs()[0];
+    assert_eq!(leg.from_name, "Philadelphia 30th Street");
+    assert_eq!(leg.to_code, "HAR");
+    assert_eq!(leg.estimated_departure, None);
+    assert_eq!(leg.delay_minutes, Some(0));
+    assert_eq!(itineraries[0].transfers(), 0);
+    assert_eq!(itineraries[0].duration(), Duration::minutes(110));
+
+    // Wrong direction
+    assert_eq!(trains.trips("HAR", "PGH").len(), 1);
+    assert!(trains.trips("PGH", "HAR").is_empty());
+    assert!(trains.trips("phl", "xyz").is_empty());
+}
+
+#[test]
+fn test_direct_include_departed() {
+    let trains = common::trains();
+    let planner = TripPlanner::new().include_departed(true);
+
+    let itineraries = planner.direct(&trains, "phl", "har");
+    assert_eq!(train_ids(&itineraries), [["657-29"], ["43-30"]]);
+
+    let leg = &itineraries[0].legs()[0];
+    assert_eq!(leg.expected_departure(), leg.estimated_departure.unwrap());
+    assert_eq!(leg.delay_minutes, Some(5));
+    assert_eq!(leg.duration(), Duration::minutes(100));
+}
+
+#[test]
+fn test_one_transfer() {
+    let trains = common::trains();
+
+    assert!(trains.trips("WIL", "LNC").is_empty());
+
+    let itineraries = TripPlanner::new().one_transfer(&trains, "WIL", "LNC");
+    assert_eq!(train_ids(&itineraries), [["94-29", "43-30"]]);
+
+    let itinerary = &itineraries[0];
+    assert_eq!(itinerary.transfers(), 1);
+    assert_eq!(itinerary.legs()[0].to_code, "PHL");
+    assert_eq!(itinerary.legs()[1].from_code, "PHL");
+    assert_eq!(
+        itinerary.connection_times(),
+        [Duration::hours(13) + Duration::minutes(40)]
+    );
+    assert_eq!(
+        itinerary.departure(),
+        itinerary.legs()[0].expected_departure()
+    );
+    assert_eq!(itinerary.arrival(), itinerary.legs()[1].scheduled_arrival);
+}
+
+#[test]
+fn test_one_transfer_min_connection() {
+    let trains = common::trains();
+
+    let planner = TripPlanner::new().min_connection(Duration::hours(14));
+    assert!(planner.one_transfer(&trains, "WIL", "LNC").is_empty());
+
+    // The first train already departed from Baltimore
+    assert!(TripPlanner::new()
+        .one_transfer(&trains, "BAL", "LNC")
+        .is_empty());
+    let planner = TripPlanner::new().include_departed(true);
+    assert_eq!(
+        train_ids(&planner.one_transfer(&trains, "BAL", "LNC")),
+        [["94-29", "43-30"]]
+    );
+}
+
+#[test]
+fn test_plan() {
+    let trains = common::trains();
+    let planner = TripPlanner::new().include_departed(true);
+
+    let itineraries = planner.plan(&trains, "TRE", "HAR");
+    assert_eq!(
+        train_ids(&itineraries),
+        [
+            vec!["657-29"],
+            vec!["612-29", "657-29"],
+            vec!["612-29", "43-30"],
+            vec!["94-29", "43-30"],
+        ]
+    );
+
+    // The direct train wins the tie on arrival
+    assert_eq!(itineraries[0].arrival(), itineraries[1].arrival());
+    assert_eq!(itineraries[1].legs()[0].to_code, "NWK");
+    assert!(itineraries[1].connection_times()[0] >= Duration::minutes(15));
+}
+
+#[test]
+fn test_itinerary_new() {
+    assert!(Itinerary::new(Vec::new()).is_none());
+
+    let direct = &common::trains().trips("PHL", "HAR")[0];
+    let itinerary = Itinerary::new(direct.legs().to_vec()).unwrap();
+    assert_eq!(&itinerary, direct);
+    assert_eq!(itinerary.transfers(), 0);
+}
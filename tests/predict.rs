@@ -0,0 +1,235 @@
+mod common;
+
+use amtrak_api::{
+    export::StopEvent,
+    predict::{backtest, ArrivalPredictor, BaselinePredictor, Prediction, RunTimes},
+    responses::{Train, TrainStatus},
+};
+use chrono::{DateTime, Duration, FixedOffset};
+
+fn train(response: amtrak_api::responses::TrainResponse, train_id: &str) -> Train {
+    response
+        .0
+        .into_values()
+        .flatten()
+        .find(|train| train.train_id == train_id)
+        .unwrap()
+}
+
+fn time(value: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(value).unwrap()
+}
+
+fn predicted(predictions: &[Prediction], station_code: &str) -> DateTime<FixedOffset> {
+    predictions
+        .iter()
+        .find(|prediction| prediction.station_code == station_code)
+        .unwrap()
+        .predicted_arrival
+}
+
+/// Predicts the published estimate, used to check the trait can be plugged
+/// into the backtest
+struct PublishedPredictor;
+
+impl ArrivalPredictor for PublishedPredictor {
+    fn predict(&self, train: &Train, now: DateTime<FixedOffset>) -> Vec<Prediction> {
+        train
+            .remaining_stations()
+            .map(|station| Prediction {
+                station_code: station.code.clone(),
+                scheduled_arrival: station.schedule_arrival,
+                published_arrival: station.arrival,
+                predicted_arrival: station.arrival.unwrap_or(now),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_run_times() {
+    let events: Vec<StopEvent> = train(common::trains(), "612-29").stop_events().collect();
+    let mut run_times = RunTimes::new();
+    run_times.add_run(&events);
+
+    assert_eq!(
+        run_times.run_time("HAR", "LNC"),
+        Some(Duration::minutes(36))
+    );
+    assert_eq!(
+        run_times.run_time("ARD", "PHL"),
+        Some(Duration::minutes(19))
+    );
+    assert_eq!(run_times.dwell_time("LNC"), Some(Duration::minutes(2)));
+
+    // The train is still standing at Philadelphia
+    assert_eq!(run_times.dwell_time("PHL"), None);
+    assert_eq!(run_times.run_time("PHL", "TRE"), None);
+    assert_eq!(run_times.run_time("LNC", "HAR"), None);
+}
+
+#[test]
+fn test_predict_from_delay() {
+    let keystone = train(common::trains(), "657-29");
+    let predictions = BaselinePredictor::default().predict(&keystone, common::now());
+
+    let codes: Vec<_> = predictions
+        .iter()
+        .map(|prediction| prediction.station_code.as_str())
+        .collect();
+    assert_eq!(codes, ["PAO", "LNC", "HAR"]);
+
+    assert_eq!(
+        predictions[0].predicted_arrival,
+        time("2023-08-29T22:34:00-04:00")
+    );
+    assert_eq!(
+        predictions[0].published_arrival,
+        Some(time("2023-08-29T22:34:00-04:00"))
+    );
+    assert_eq!(
+        predicted(&predictions, "LNC"),
+        time("2023-08-29T23:15:00-04:00")
+    );
+    assert_eq!(
+        predicted(&predictions, "HAR"),
+        time("2023-08-29T23:50:00-04:00")
+    );
+}
+
+#[test]
+fn test_predict_from_velocity() {
+    let keystone = train(common::trains(), "657-29");
+    let predictor = BaselinePredictor::default().with_stations(&common::stations());
+    let predictions = predictor.predict(&keystone, common::now());
+
+    // About 5.3 miles left to Paoli at 62.5 mph from the last position report
+    let paoli = predicted(&predictions, "PAO");
+    assert!(paoli > time("2023-08-29T22:33:30-04:00"));
+    assert!(paoli < time("2023-08-29T22:34:30-04:00"));
+
+    // The minute of scheduled dwell then the scheduled run time to Lancaster
+    assert_eq!(
+        predicted(&predictions, "LNC"),
+        paoli + Duration::minutes(1) + Duration::minutes(40)
+    );
+}
+
+#[test]
+fn test_predict_from_history() {
+    let keystone = train(common::trains(), "657-29");
+    let mut history = train(common::trains(), "657-29");
+    for station in &mut history.stations {
+        station.status = TrainStatus::Departed;
+    }
+    history.stations[5].arrival = Some(time("2023-08-29T22:35:00-04:00"));
+    history.stations[5].departure = Some(time("2023-08-29T22:38:00-04:00"));
+
+    let mut run_times = RunTimes::new();
+    run_times.add_run(&history.stop_events().collect::<Vec<_>>());
+    let predictions = BaselinePredictor::new(run_times).predict(&keystone, common::now());
+
+    // Departed Ardmore at 22:23 and usually takes 12 minutes to Paoli, then
+    // dwells 3 minutes and takes 37 minutes to Lancaster
+    assert_eq!(
+        predicted(&predictions, "PAO"),
+        time("2023-08-29T22:35:00-04:00")
+    );
+    assert_eq!(
+        predicted(&predictions, "LNC"),
+        time("2023-08-29T23:15:00-04:00")
+    );
+}
+
+#[test]
+fn test_predict_at_station() {
+    let keystone = train(common::trains(), "612-29");
+    let predictions = BaselinePredictor::default().predict(&keystone, common::now());
+
+    assert_eq!(
+        predicted(&predictions, "PHL"),
+        time("2023-08-29T17:58:00-04:00")
+    );
+    // Stands ten minutes in Philadelphia then runs 33 minutes to Trenton
+    assert_eq!(
+        predicted(&predictions, "TRE"),
+        time("2023-08-29T18:41:00-04:00")
+    );
+}
+
+#[test]
+fn test_predict_never_in_the_past() {
+    let keystone = train(common::trains(), "657-29");
+    let now = time("2023-08-29T22:45:00-04:00");
+    let predictions = BaselinePredictor::default().predict(&keystone, now);
+
+    assert_eq!(predicted(&predictions, "PAO"), now);
+}
+
+#[test]
+fn test_predict_predeparture() {
+    let pennsylvanian = train(common::trains(), "43-30");
+    let predictions = BaselinePredictor::default().predict(&pennsylvanian, common::now());
+
+    assert_eq!(predictions.len(), pennsylvanian.stations.len());
+    assert_eq!(
+        predictions[0].predicted_arrival,
+        predictions[0].scheduled_arrival
+    );
+    assert!(predictions
+        .iter()
+        .all(|prediction| prediction.published_arrival.is_none()));
+}
+
+#[test]
+fn test_backtest() {
+    let snapshot = train(common::trains(), "657-29");
+    let mut outcome = train(common::later_trains(), "657-29");
+    outcome.stations[5].arrival = Some(time("2023-08-29T22:36:00-04:00"));
+    let outcome: Vec<StopEvent> = outcome.stop_events().collect();
+
+    let report = backtest(
+        &BaselinePredictor::default(),
+        [(&snapshot, outcome.as_slice())],
+    );
+    assert_eq!(report.samples, 1);
+    assert_eq!(report.mean_absolute_error_minutes, 2.0);
+    assert_eq!(report.published_samples, 1);
+    assert_eq!(report.published_mean_absolute_error_minutes, 2.0);
+
+    let predictor: Box<dyn ArrivalPredictor> = Box::new(PublishedPredictor);
+    let report = backtest(predictor.as_ref(), [(&snapshot, outcome.as_slice())]);
+    assert_eq!(report.samples, 1);
+    assert_eq!(
+        report.mean_absolute_error_minutes,
+        report.published_mean_absolute_error_minutes
+    );
+
+    // Nothing to compare to before the train reaches its next station
+    let outcome: Vec<StopEvent> = snapshot.stop_events().collect();
+    let report = backtest(
+        &BaselinePredictor::default(),
+        [(&snapshot, outcome.as_slice())],
+    );
+    assert_eq!(report.samples, 0);
+    assert_eq!(report.mean_absolute_error_minutes, 0.0);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_backtest_runs() {
+    use amtrak_api::{predict::backtest_runs, storage::Store};
+
+    let mut store = Store::open_in_memory().unwrap();
+    store.ingest(&common::trains()).unwrap();
+    store.ingest(&common::later_trains()).unwrap();
+
+    let runs = store.runs(657, common::now() - Duration::days(1)).unwrap();
+    let predictor = BaselinePredictor::new(RunTimes::from_runs(&runs));
+    let report = backtest_runs(&predictor, &store, &runs).unwrap();
+
+    // Only the first snapshot has Paoli ahead of the train
+    assert_eq!(report.samples, 1);
+    assert_eq!(report.published_samples, 1);
+    assert_eq!(report.published_mean_absolute_error_minutes, 0.0);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use amtrak_api::{
+    geo::{BoundingBox, Coordinate},
+    query::TrainQuery,
+    responses::{Heading, TrainState},
+};
+use chrono::Duration;
+
+/// Returns the sorted ids of the fixture trains matching the query
+fn run(query: TrainQuery) -> Vec<String> {
+    let response = common::trains();
+    let mut ids: Vec<_> = query
+        .run(&response)
+        .map(|train| train.train_id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_empty_query() {
+    assert_eq!(run(TrainQuery::new()).len(), 5);
+}
+
+#[test]
+fn test_route_name() {
+    assert_eq!(
+        run(TrainQuery::new().route_name("keystone")),
+        ["612-29", "657-29"]
+    );
+    assert!(run(TrainQuery::new().route_name("Acela")).is_empty());
+}
+
+#[test]
+fn test_train_num() {
+    assert_eq!(run(TrainQuery::new().train_num(94)), ["94-29"]);
+}
+
+#[test]
+fn test_origin() {
+    assert_eq!(run(TrainQuery::new().origin("HAR")), ["612-29"]);
+}
+
+#[test]
+fn test_destination() {
+    assert_eq!(run(TrainQuery::new().destination("har")), ["657-29"]);
+}
+
+#[test]
+fn test_state() {
+    assert_eq!(
+        run(TrainQuery::new().state(TrainState::Predeparture)),
+        ["43-30"]
+    );
+    assert!(run(TrainQuery::new().state(TrainState::Completed)).is_empty());
+}
+
+#[test]
+fn test_stops_at() {
+    assert_eq!(
+        run(TrainQuery::new().stops_at("lnc")),
+        ["43-30", "612-29", "657-29"]
+    );
+}
+
+#[test]
+fn test_delayed_more_than() {
+    assert_eq!(
+        run(TrainQuery::new().delayed_more_than(Duration::minutes(5))),
+        ["94-29"]
+    );
+    assert!(run(TrainQuery::new().delayed_more_than(Duration::minutes(20))).is_empty());
+}
+
+#[test]
+fn test_heading() {
+    assert_eq!(run(TrainQuery::new().heading(Heading::W)), ["657-29"]);
+}
+
+#[test]
+fn test_within() {
+    let pennsylvania = BoundingBox::new(
+        Coordinate::new(39.72, -80.52),
+        Coordinate::new(42.27, -74.69),
+    );
+
+    assert_eq!(
+        run(TrainQuery::new().within(pennsylvania)),
+        ["612-29", "657-29", "94-29"]
+    );
+}
+
+#[test]
+fn test_matching() {
+    assert_eq!(
+        run(TrainQuery::new().matching(|train| train.velocity > 60.0)),
+        ["657-29"]
+    );
+}
+
+#[test]
+fn test_combined() {
+    let query = TrainQuery::new()
+        .route_name("Keystone")
+        .state(TrainState::Active)
+        .heading(Heading::E);
+    assert_eq!(run(query), ["612-29"]);
+
+    let predicate = TrainQuery::new()
+        .stops_at("NYP")
+        .train_num(43)
+        .into_predicate();
+    let response = common::trains();
+    assert_eq!(
+        response
+            .0
+            .values()
+            .flatten()
+            .filter(|t| predicate(t))
+            .count(),
+        1
+    );
+}
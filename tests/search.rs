@@ -0,0 +1,89 @@
+mod common;
+
+use amtrak_api::search::{StationMatch, StationSearch};
+
+fn codes<'a>(matches: &[StationMatch<'a>]) -> Vec<&'a str> {
+    matches
+        .iter()
+        .map(|found| found.station.code.as_str())
+        .collect()
+}
+
+#[test]
+fn test_search_code() {
+    let stations = common::stations();
+
+    let matches = stations.search("phl");
+    assert_eq!(codes(&matches)[0], "PHL");
+    assert_eq!(matches[0].score, 1.0);
+}
+
+#[test]
+fn test_search_name_and_city() {
+    let stations = common::stations();
+
+    assert_eq!(codes(&stations.search("Philadelphia")), ["PHL"]);
+    assert_eq!(codes(&stations.search("phila")), ["PHL"]);
+    assert_eq!(codes(&stations.search("philadelpia")), ["PHL"]);
+    assert_eq!(codes(&stations.search("30th street")), ["PHL"]);
+    assert_eq!(codes(&stations.search("30th st")), ["PHL"]);
+    assert_eq!(codes(&stations.search("Toronto")), ["TWO"]);
+    assert_eq!(codes(&stations.search("new york"))[0], "NYP");
+}
+
+#[test]
+fn test_search_aliases() {
+    let stations = common::stations();
+
+    let matches = stations.search("philly");
+    assert_eq!(codes(&matches), ["PHL"]);
+    assert!(matches[0].score < 1.0);
+
+    assert_eq!(codes(&stations.search("NYC")), ["NYP"]);
+    assert_eq!(codes(&stations.search("dc"))[0], "WAS");
+    assert_eq!(codes(&stations.search("union station"))[0], "WAS");
+}
+
+#[test]
+fn test_search_penn() {
+    let stations = common::stations();
+
+    let matches = stations.search("penn station");
+    assert_eq!(codes(&matches), ["NYP", "NWK", "BAL"]);
+    assert!(matches
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+
+    assert_eq!(codes(&stations.search("penn")), ["NYP", "NWK", "BAL"]);
+    assert_eq!(codes(&stations.search("penn station baltimore"))[0], "BAL");
+    assert_eq!(codes(&stations.search("newark penn"))[0], "NWK");
+    assert_eq!(codes(&stations.search("balt pen")), ["BAL"]);
+}
+
+#[test]
+fn test_search_custom_alias() {
+    let stations = common::stations();
+    let search = StationSearch::new()
+        .with_alias("penn station", &["BAL", "NYP"])
+        .with_alias("the capital", &["SAC"]);
+
+    assert_eq!(codes(&search.search(&stations, "penn station"))[0], "BAL");
+    assert_eq!(codes(&search.search(&stations, "the capital")), ["SAC"]);
+}
+
+#[test]
+fn test_search_custom_abbreviation() {
+    let stations = common::stations();
+    let search = StationSearch::new().with_abbreviation("pgh", &["pittsburgh"]);
+
+    assert_eq!(codes(&search.search(&stations, "pgh"))[0], "PGH");
+}
+
+#[test]
+fn test_search_no_match() {
+    let stations = common::stations();
+
+    assert!(stations.search("").is_empty());
+    assert!(stations.search("  -- ").is_empty());
+    assert!(stations.search("chicago").is_empty());
+}
@@ -0,0 +1,323 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use amtrak_api::{server::Server, Client};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Starts the server on a random local port and returns its URL
+async fn spawn(server: Server) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(server.serve(listener));
+
+    url
+}
+
+/// Returns a server whose cache is filled with the fixtures
+fn fixture_server() -> Server {
+    let server = Server::new(Client::with_base_url("http://127.0.0.1:1"));
+    server.cache().set_trains(common::trains());
+    server.cache().set_stations(common::stations());
+
+    server
+}
+
+async fn get(url: &str) -> (StatusCode, Value) {
+    let response = reqwest::get(url).await.unwrap();
+    let status = response.status();
+
+    (status, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn test_trains() {
+    let url = spawn(fixture_server()).await;
+
+    let (status, body) = get(&format!("{}/trains", url)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let train_ids: Vec<_> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|train| train["train_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        train_ids,
+        vec!["43-30", "538-29", "612-29", "657-29", "94-29"]
+    );
+}
+
+#[tokio::test]
+async fn test_train() {
+    let url = spawn(fixture_server()).await;
+
+    let (status, body) = get(&format!("{}/trains/657-29", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["train_num"], 657);
+    assert_eq!(body["route_name"], "Keystone");
+
+    let (status, body) = get(&format!("{}/trains/657-1", url)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_board() {
+    let url = spawn(fixture_server()).await;
+
+    let (status, body) = get(&format!("{}/stations/phl/board", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["station_code"], "PHL");
+
+    let train_ids: Vec<_> = body["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["train_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(train_ids, vec!["612-29", "94-29", "43-30"]);
+
+    let (status, body) = get(&format!("{}/stations/XYZ/board", url)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"].is_string());
+}
+
+/// Returns the keys of the object, and of every object nested in it
+fn keys(value: &Value) -> Vec<String> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .flat_map(|(key, value)| std::iter::once(key.clone()).chain(keys(value)))
+            .collect(),
+        Value::Array(values) => values.iter().flat_map(keys).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_snake_case_keys() {
+    let url = spawn(fixture_server()).await;
+
+    for path in ["/trains", "/trains/657-29", "/stations/PHL/board"] {
+        let (status, body) = get(&format!("{}{}", url, path)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let keys = keys(&body);
+        assert!(!keys.is_empty());
+        for key in keys {
+            assert!(
+                key.chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+                "{} returned the key {}",
+                path,
+                key
+            );
+        }
+    }
+
+    // The GeoJSON properties keep the camelCase keys of Train::to_geojson
+    let (status, body) = get(&format!("{}/geojson", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    for key in keys(&body) {
+        assert!(
+            key.starts_with(|c: char| c.is_ascii_lowercase())
+                && key.chars().all(|c| c.is_ascii_alphanumeric()),
+            "/geojson returned the key {}",
+            key
+        );
+    }
+    let features = body["features"].as_array().unwrap();
+    assert_eq!(features.len(), 5);
+    assert!(features
+        .iter()
+        .any(|feature| feature["properties"]["trainId"] == "657-29"));
+
+    let (_, train) = get(&format!("{}/trains/657-29", url)).await;
+    assert_eq!(train["train_id"], "657-29");
+    assert_eq!(train["last_value"], "2023-08-29T22:28:45-04:00");
+    assert!(train["object_id"].is_u64());
+    assert_eq!(
+        train["stations"][0]["schedule_arrival"],
+        "2023-08-29T20:30:00-04:00"
+    );
+}
+
+#[tokio::test]
+async fn test_geojson() {
+    let url = spawn(fixture_server()).await;
+
+    let response = reqwest::get(format!("{}/geojson", url)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/geo+json");
+
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["type"], "FeatureCollection");
+    assert_eq!(body["features"].as_array().unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_base_path() {
+    let url = spawn(fixture_server().with_base_path("amtrak/v1/")).await;
+
+    let (status, _) = get(&format!("{}/amtrak/v1/trains", url)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = reqwest::get(format!("{}/trains", url)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_not_fetched_yet() {
+    let url = spawn(Server::new(Client::with_base_url("http://127.0.0.1:1"))).await;
+
+    let (status, body) = get(&format!("{}/trains", url)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_refresh_normalizes_empty_response() {
+    let mut mock = mockito::Server::new_async().await;
+    let trains_mock = mock
+        .mock("GET", "/trains")
+        .with_body("[]")
+        .expect_at_least(1)
+        .create_async()
+        .await;
+    let stations_mock = mock
+        .mock("GET", "/stations")
+        .with_body(common::STATIONS_JSON)
+        .expect_at_least(1)
+        .create_async()
+        .await;
+
+    let server = Server::new(Client::with_base_url(&mock.url()));
+    server.refresh().await.unwrap();
+    let url = spawn(server).await;
+
+    let (status, body) = get(&format!("{}/trains", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, Value::Array(Vec::new()));
+
+    let (status, body) = get(&format!("{}/stations/PHL/board", url)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["entries"], Value::Array(Vec::new()));
+
+    trains_mock.assert_async().await;
+    stations_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_events_sse() {
+    let server = fixture_server();
+    let cache = server.cache().clone();
+    let url = spawn(server).await;
+
+    let mut response = reqwest::get(format!(
+        "{}/events?routes=Keystone,Capitol%20Corridor&stations=PAO",
+        url
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    cache.set_trains(common::later_trains());
+
+    let mut body = String::new();
+    while body.matches("\n\n").count() < 2 {
+        let chunk = response.chunk().await.unwrap().unwrap();
+        body.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+
+    let events: Vec<_> = body
+        .split("\n\n")
+        .filter(|event| !event.is_empty())
+        .map(|event| {
+            let name = event.lines().find_map(|line| line.strip_prefix("event: "));
+            let data = event.lines().find_map(|line| line.strip_prefix("data: "));
+            let data: Value = serde_json::from_str(data.unwrap()).unwrap();
+            (name.unwrap().to_string(), data)
+        })
+        .collect();
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].0, "arrival");
+    assert_eq!(events[0].1["train_id"], "657-29");
+    assert_eq!(events[1].0, "delay_change");
+    assert_eq!(events[1].1["delay_minutes"], 7);
+}
+
+#[tokio::test]
+async fn test_events_websocket() {
+    let server = fixture_server();
+    let cache = server.cache().clone();
+    let url = spawn(server).await;
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(format!(
+        "{}/ws?train_ids=94-29",
+        url.replace("http", "ws")
+    ))
+    .await
+    .unwrap();
+
+    async fn next_event(
+        socket: &mut tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+    ) -> Value {
+        loop {
+            if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    cache.set_trains(common::later_trains());
+    let event = next_event(&mut socket).await;
+    assert_eq!(event["type"], "departure");
+    assert_eq!(event["train_id"], "94-29");
+    assert_eq!(event["station_code"], "WIL");
+
+    // Replace the subscription, then replay the same changes
+    socket
+        .send(Message::Text(r#"{"stations": ["EMY"]}"#.into()))
+        .await
+        .unwrap();
+    socket.send(Message::Text("not json".into())).await.unwrap();
+    assert!(next_event(&mut socket).await["error"].is_string());
+
+    cache.set_trains(common::trains());
+    cache.set_trains(common::later_trains());
+
+    let event = next_event(&mut socket).await;
+    assert_eq!(event["type"], "arrival");
+    assert_eq!(event["train_id"], "538-29");
+    let event = next_event(&mut socket).await;
+    assert_eq!(event["type"], "departure");
+    assert_eq!(event["station_code"], "EMY");
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let url = spawn(fixture_server()).await;
+
+    let (status, _) = get(&format!("{}/trains", url)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let response = reqwest::get(format!("{}/metrics", url)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let text = response.text().await.unwrap();
+    assert!(text.contains("amtrak_active_trains{route=\"Keystone\"} 2\n"));
+    assert!(text.contains("amtrak_trains{state=\"Active\"} 4\n"));
+    assert!(text.contains("amtrak_cache_hits_total 1\n"));
+
+    // The poller could not reach the API
+    assert!(text.contains("amtrak_client_errors_total{variant=\"RequestFailed\"}"));
+}
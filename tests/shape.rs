@@ -0,0 +1,113 @@
+mod common;
+
+use amtrak_api::{
+    geo::Coordinate,
+    responses::Train,
+    shape::{RouteShape, RouteShapes},
+};
+use serde_json::json;
+
+fn train(train_id: &str) -> Train {
+    common::trains()
+        .0
+        .into_values()
+        .flatten()
+        .find(|train| train.train_id == train_id)
+        .unwrap()
+}
+
+/// Returns the Keystone shape, drawn through the stations of train 657
+fn keystone() -> RouteShapes {
+    let route = train("657-29")
+        .route_to_geojson(&common::stations())
+        .unwrap();
+
+    RouteShapes::from_geojson(&json!({
+        "type": "FeatureCollection",
+        "features": [route],
+    }))
+}
+
+fn distance_to(progress: &amtrak_api::shape::RouteProgress, station_code: &str) -> f64 {
+    progress
+        .stations
+        .iter()
+        .find(|station| station.station_code == station_code)
+        .unwrap()
+        .distance
+}
+
+#[test]
+fn test_snap() {
+    let shape = RouteShape::new(vec![
+        Coordinate::new(40.0, -75.0),
+        Coordinate::new(40.0, -76.0),
+        Coordinate::new(41.0, -76.0),
+    ]);
+    let first_leg = shape.points()[0].distance_to(shape.points()[1]);
+    assert!((shape.length() - first_leg - 69.1).abs() < 0.1);
+
+    // Halfway along the first segment, a little north of it
+    let snapped = shape.snap(Coordinate::new(40.01, -75.5)).unwrap();
+    assert!((snapped.coordinate.lat - 40.0).abs() < 1e-9);
+    assert!((snapped.coordinate.lon + 75.5).abs() < 1e-9);
+    assert!((snapped.distance_along - first_leg / 2.0).abs() < 0.1);
+    assert!((snapped.offset - 0.69).abs() < 0.01);
+
+    // Past the end of the shape
+    let snapped = shape.snap(Coordinate::new(42.0, -76.0)).unwrap();
+    assert_eq!(snapped.coordinate, Coordinate::new(41.0, -76.0));
+    assert_eq!(snapped.distance_along, shape.length());
+
+    assert!(RouteShape::new(Vec::new())
+        .snap(snapped.coordinate)
+        .is_none());
+}
+
+#[test]
+fn test_from_geojson() {
+    let shapes = keystone();
+    assert_eq!(shapes.len(), 1);
+    assert_eq!(shapes.get("Keystone").unwrap().points().len(), 8);
+    assert!(shapes.get("Pennsylvanian").is_none());
+
+    let shapes = RouteShapes::from_geojson(&train("657-29").to_geojson());
+    assert!(shapes.is_empty());
+}
+
+#[test]
+fn test_progress() {
+    let keystone_shape = keystone();
+    let stations = common::stations();
+    let train = train("657-29");
+    let shape = keystone_shape.get("Keystone").unwrap();
+
+    let progress = train.progress(&keystone_shape, &stations).unwrap();
+    assert!(progress.snapped.offset < 1.0);
+    assert!(
+        (progress.distance_travelled + progress.distance_remaining - shape.length()).abs() < 1e-6
+    );
+
+    let paoli = distance_to(&progress, "PAO");
+    assert!(paoli > 4.0 && paoli < 6.0, "{} miles to Paoli", paoli);
+    assert!(distance_to(&progress, "ARD") < 0.0);
+    assert!((distance_to(&progress, "NYP") + progress.distance_travelled).abs() < 1e-6);
+    assert!((distance_to(&progress, "HAR") - progress.distance_remaining).abs() < 1e-6);
+    assert_eq!(progress.stations.len(), train.stations.len());
+}
+
+#[test]
+fn test_progress_reverse_direction() {
+    let shapes = keystone();
+    let stations = common::stations();
+
+    // Train 612 runs the Keystone shape from Harrisburg to New York
+    let progress = train("612-29").progress(&shapes, &stations).unwrap();
+    assert!(distance_to(&progress, "PHL").abs() < 0.1);
+    assert!(distance_to(&progress, "TRE") > 0.0);
+    assert!(distance_to(&progress, "LNC") < 0.0);
+    assert!((progress.distance_travelled + distance_to(&progress, "HAR")).abs() < 1e-6);
+    assert!((progress.distance_remaining - distance_to(&progress, "NYP")).abs() < 1e-6);
+
+    assert!(train("43-30").progress(&shapes, &stations).is_none());
+}
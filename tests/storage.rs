@@ -0,0 +1,187 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use amtrak_api::{
+    responses::{TrainResponse, TrainStatus},
+    storage::{IngestSummary, Store},
+};
+use chrono::Duration;
+
+/// Returns the Keystone 657 of the fixture as it ran a month earlier
+fn previous_month() -> TrainResponse {
+    let mut response = common::trains();
+    response.0.retain(|_, trains| {
+        trains.retain(|train| train.train_id == "657-29");
+        !trains.is_empty()
+    });
+
+    for train in response.0.values_mut().flatten() {
+        let shift = -Duration::days(31);
+        train.object_id += 1000;
+        train.created_at += shift;
+        train.updated_at += shift;
+        train.last_value += shift;
+        for station in &mut train.stations {
+            station.schedule_arrival += shift;
+            station.schedule_departure += shift;
+            station.arrival = station.arrival.map(|arrival| arrival + shift);
+            station.departure = station.departure.map(|departure| departure + shift);
+        }
+    }
+
+    response
+}
+
+#[test]
+fn test_migrations() {
+    let path = std::env::temp_dir().join(format!("amtrak-api-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // Only opening an existing database fails when there is none yet
+    assert!(matches!(
+        Store::open_existing(&path),
+        Err(amtrak_api::errors::Error::SqliteFailed(_))
+    ));
+    assert!(!path.exists());
+
+    let mut store = Store::open(&path).unwrap();
+    assert_eq!(store.schema_version().unwrap(), 1);
+    store.ingest(&common::trains()).unwrap();
+    drop(store);
+
+    // Reopening an up to date database keeps its data
+    let store = Store::open_existing(&path).unwrap();
+    assert_eq!(store.schema_version().unwrap(), 1);
+    assert!(store.latest("657-29").unwrap().is_some());
+    drop(store);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_ingest_deduplicates() {
+    let mut store = Store::open_in_memory().unwrap();
+
+    assert_eq!(
+        store.ingest(&common::trains()).unwrap(),
+        IngestSummary {
+            inserted: 5,
+            duplicates: 0
+        }
+    );
+    assert_eq!(
+        store.ingest(&common::trains()).unwrap(),
+        IngestSummary {
+            inserted: 0,
+            duplicates: 5
+        }
+    );
+    assert_eq!(
+        store.ingest(&common::later_trains()).unwrap(),
+        IngestSummary {
+            inserted: 3,
+            duplicates: 2
+        }
+    );
+}
+
+#[test]
+fn test_runs() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.ingest(&previous_month()).unwrap();
+    store.ingest(&common::trains()).unwrap();
+    store.ingest(&common::later_trains()).unwrap();
+
+    let runs = store.runs(657, common::now() - Duration::days(30)).unwrap();
+    assert_eq!(runs.len(), 1);
+
+    let run = &runs[0];
+    assert_eq!(run.train_id, "657-29");
+    assert_eq!(run.route_name, "Keystone");
+    assert_eq!(
+        run.departure,
+        run.train.stations[0].schedule_departure.to_utc()
+    );
+    assert_eq!(run.train.train_timely, "7 Minutes Late");
+
+    let paoli = &run.stop_events[5];
+    assert_eq!(paoli.station_code, "PAO");
+    assert_eq!(paoli.stop_sequence, 6);
+    assert_eq!(paoli.status, TrainStatus::Station);
+    assert_eq!(run.stop_events.len(), run.train.stations.len());
+
+    let runs = store.runs(657, common::now() - Duration::days(60)).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert!(runs[0].departure < runs[1].departure);
+
+    assert!(store
+        .runs(658, common::now() - Duration::days(60))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        store
+            .all_runs(common::now() - Duration::days(30))
+            .unwrap()
+            .len(),
+        5
+    );
+}
+
+#[test]
+fn test_out_of_order_ingest() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.ingest(&common::later_trains()).unwrap();
+    store.ingest(&common::trains()).unwrap();
+
+    let runs = store.runs(94, common::now() - Duration::days(1)).unwrap();
+    let wilmington = runs[0]
+        .stop_events
+        .iter()
+        .find(|event| event.station_code == "WIL")
+        .unwrap();
+    assert_eq!(wilmington.status, TrainStatus::Departed);
+
+    let latest = store.latest("94-29").unwrap().unwrap();
+    assert_eq!(
+        latest.updated_at,
+        common::trains().0["94"][0].updated_at + Duration::minutes(3)
+    );
+}
+
+#[test]
+fn test_positions() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.ingest(&common::trains()).unwrap();
+    store.ingest(&common::later_trains()).unwrap();
+
+    let positions = store
+        .positions("657-29", common::now() - Duration::hours(1))
+        .unwrap();
+    assert_eq!(positions.len(), 2);
+    assert!(positions[0].recorded_at < positions[1].recorded_at);
+    assert_eq!(positions[0].velocity, 62.5);
+
+    let train = &common::trains().0["657"][0];
+    assert_eq!(positions[0].heading, train.heading);
+    assert_eq!(positions[0].coordinate, train.coordinate());
+
+    assert!(store
+        .positions("612-29", common::now() - Duration::hours(1))
+        .unwrap()
+        .is_empty());
+    assert!(store.latest("1-1").unwrap().is_none());
+}
+
+#[test]
+fn test_snapshots() {
+    let mut store = Store::open_in_memory().unwrap();
+    store.ingest(&common::later_trains()).unwrap();
+    store.ingest(&common::trains()).unwrap();
+
+    let runs = store.runs(657, common::now() - Duration::days(1)).unwrap();
+    let snapshots = store.snapshots(&runs[0]).unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[0].train_timely, "5 Minutes Late");
+    assert_eq!(snapshots[1].train_timely, "7 Minutes Late");
+}
@@ -1,10 +1,12 @@
+mod common;
+
 use amtrak_api::{responses::TrainStatus, Client};
 use chrono::{FixedOffset, NaiveDate};
 use mockito::Server;
 
 #[tokio::test]
 async fn test_single_train() -> Result<(), amtrak_api::errors::Error> {
-    let mut server = Server::new();
+    let mut server = Server::new_async().await;
     let mock_server = server
         .mock("GET", "/trains")
         .with_body(
@@ -271,7 +273,7 @@ async fn test_single_train() -> Result<(), amtrak_api::errors::Error> {
 
     assert_eq!(trains.len(), 1);
 
-    let train = trains.get(0).unwrap();
+    let train = trains.first().unwrap();
 
     assert_eq!(train.route_name, "Keystone");
     assert_eq!(train.train_num, 657);
@@ -333,7 +335,7 @@ async fn test_single_train() -> Result<(), amtrak_api::errors::Error> {
 
 #[tokio::test]
 async fn test_empty_trains() -> Result<(), amtrak_api::errors::Error> {
-    let mut server = Server::new();
+    let mut server = Server::new_async().await;
     let mock_server = server
         .mock("GET", "/trains")
         .with_body("[]")
@@ -348,3 +350,37 @@ async fn test_empty_trains() -> Result<(), amtrak_api::errors::Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_delay() {
+    let response = common::trains();
+
+    let train = &response.0["94"][0];
+    assert_eq!(train.delay(), Some(chrono::Duration::minutes(20)));
+    assert_eq!(
+        train.stations[2].arrival_delay(),
+        Some(chrono::Duration::minutes(18))
+    );
+
+    let train = &response.0["538"][0];
+    assert_eq!(
+        train.stations[1].arrival_delay(),
+        Some(chrono::Duration::minutes(-1))
+    );
+    assert_eq!(
+        train.stations[1].departure_delay(),
+        Some(chrono::Duration::zero())
+    );
+
+    // Predeparture trains have empty comments
+    let train = &response.0["43"][0];
+    assert_eq!(train.stations[0].arrival_delay(), None);
+
+    let mut train = train.clone();
+    train.train_timely = "1 Hours, 5 Minutes Late".to_string();
+    assert_eq!(train.delay(), Some(chrono::Duration::minutes(65)));
+    train.train_timely = "NaN Minutes Early".to_string();
+    assert_eq!(train.delay(), None);
+    train.train_timely = "Unknown".to_string();
+    assert_eq!(train.delay(), None);
+}